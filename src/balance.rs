@@ -0,0 +1,32 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{DispatchCallback, Element, Percentage};
+
+impl<T: MinusAbleMatUnit> Element<T> {
+    /// Make every window in the element get about the same area.
+    ///
+    /// Every container shares its space between the children by how many windows each child
+    /// holds, then the element is remapped. Only the windows which are changed are dispatched.
+    pub fn balance<F>(&mut self, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let before = self.windows();
+        self.balance_percent();
+        self.remap_changed(&before, f);
+    }
+
+    fn balance_percent(&mut self) {
+        let Some(way) = self.way() else {
+            return;
+        };
+        let total = self.window_count() as f32;
+        let (Self::Vertical { elements, .. } | Self::Horizontal { elements, .. }) = self else {
+            unreachable!()
+        };
+        for element in elements {
+            let share = element.window_count() as f32 / total;
+            element.set_percentage(Percentage::along(way, share));
+            element.balance_percent();
+        }
+    }
+}
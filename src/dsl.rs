@@ -0,0 +1,254 @@
+//! A short text for a tree, like `H[1:0.3, V[2, 3]]`.
+//!
+//! `H[...]` and `V[...]` are the horizontal and vertical containers, a number is a window with
+//! that id. A `:share` after an element is how much of its container it takes, the elements
+//! without it share the space left. A `!` after `H` or `V` pins the container, like
+//! [`Element::wrap`]. An empty text is an empty map.
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::layout::window;
+use crate::utils::MinusAbleMatUnit;
+use crate::{
+    Element, Error, Id, InsertWay, Percentage, Position, Result, Size, SizeAndPos, TopElementMap,
+};
+
+/// How far the shares of a container can be from the whole
+const SHARE_TOLERANCE: f32 = 1e-3;
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// Build a map in the `size_pos` from the text of a tree.
+    /// It fails at the column where the text is wrong, or where a window id is used twice
+    pub fn from_layout_str(text: &str, size_pos: SizeAndPos<T>) -> Result<Self> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            index: 0,
+            ids: HashSet::new(),
+        };
+        let mut map = TopElementMap::new(size_pos);
+        parser.skip_space();
+        if parser.peek().is_none() {
+            return Ok(map);
+        }
+        let (mut root, _) = parser.element()?;
+        parser.skip_space();
+        if parser.peek().is_some() {
+            return Err(parser.error("expected the end of the text"));
+        }
+        root.set_percentage(Size::whole());
+        root.remap(size_pos, &mut ());
+        for (id, _) in root.windows() {
+            id.reserve();
+        }
+        map.root = root;
+        Ok(map)
+    }
+}
+
+impl<T: MinusAbleMatUnit> fmt::Display for TopElementMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
+
+impl<T: MinusAbleMatUnit> fmt::Display for Element<T> {
+    /// Write the element in the text of a tree. The shares are written when they are not all
+    /// the same, in the shortest way which is read back to the same value.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyOutput(_) => Ok(()),
+            Self::Window { id, .. } => write!(f, "{}", id.0),
+            Self::Vertical {
+                elements, pinned, ..
+            }
+            | Self::Horizontal {
+                elements, pinned, ..
+            } => {
+                let way = self.way().unwrap_or_default();
+                let name = match way {
+                    InsertWay::Horizontal => 'H',
+                    InsertWay::Vertical => 'V',
+                };
+                write!(f, "{name}{}[", if *pinned { "!" } else { "" })?;
+                let even = 1. / elements.len() as f32;
+                let write_share = elements
+                    .iter()
+                    .any(|element| element.percent().on_axis(way) != even);
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.fmt(f)?;
+                    if write_share {
+                        write!(f, ":{}", element.percent().on_axis(way))?;
+                    }
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    ids: HashSet<Id>,
+}
+
+impl Parser {
+    fn error(&self, message: &'static str) -> Error {
+        Error::ParseLayout {
+            column: self.index + 1,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.index += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char, message: &'static str) -> Result<()> {
+        self.skip_space();
+        if self.peek() != Some(c) {
+            return Err(self.error(message));
+        }
+        self.index += 1;
+        Ok(())
+    }
+
+    fn number(&mut self) -> String {
+        let start = self.index;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'))
+        {
+            self.index += 1;
+        }
+        self.chars[start..self.index].iter().collect()
+    }
+
+    /// An element and its share, if it is given
+    fn element<T: MinusAbleMatUnit>(&mut self) -> Result<(Element<T>, Option<f32>)> {
+        self.skip_space();
+        let start = self.index;
+        let element = match self.peek() {
+            Some(c @ ('H' | 'V')) => {
+                self.index += 1;
+                let pinned = self.peek() == Some('!');
+                if pinned {
+                    self.index += 1;
+                }
+                let way = if c == 'H' {
+                    InsertWay::Horizontal
+                } else {
+                    InsertWay::Vertical
+                };
+                self.container(way, pinned)?
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let text = self.number();
+                let Ok(id) = text.parse() else {
+                    self.index = start;
+                    return Err(self.error("expected a window id"));
+                };
+                if !self.ids.insert(Id(id)) {
+                    self.index = start;
+                    return Err(self.error("the window id is used twice"));
+                }
+                window(Id(id))
+            }
+            _ => return Err(self.error("expected a window id, `H[` or `V[`")),
+        };
+        self.skip_space();
+        if self.peek() != Some(':') {
+            return Ok((element, None));
+        }
+        self.index += 1;
+        self.skip_space();
+        let start = self.index;
+        let share = self.number().parse::<f32>();
+        match share {
+            Ok(share) if share > 0. && share <= 1. => Ok((element, Some(share))),
+            _ => {
+                self.index = start;
+                Err(self.error("expected a share between 0 and 1"))
+            }
+        }
+    }
+
+    fn container<T: MinusAbleMatUnit>(
+        &mut self,
+        way: InsertWay,
+        pinned: bool,
+    ) -> Result<Element<T>> {
+        self.expect('[', "expected `[`")?;
+        let start = self.index;
+        let mut children = vec![self.element()?];
+        loop {
+            self.skip_space();
+            match self.peek() {
+                Some(',') => {
+                    self.index += 1;
+                    children.push(self.element()?);
+                }
+                Some(']') => {
+                    self.index += 1;
+                    break;
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+        // NOTE: the elements without share take the space left evenly
+        let given: f32 = children.iter().filter_map(|(_, share)| *share).sum();
+        let missing = children.iter().filter(|(_, share)| share.is_none()).count();
+        let rest = if missing == children.len() {
+            1. / missing as f32
+        } else {
+            (1. - given) / missing.max(1) as f32
+        };
+        let elements: Vec<Element<T>> = children
+            .into_iter()
+            .map(|(mut element, share)| {
+                element.set_percentage(Percentage::along(way, share.unwrap_or(rest)));
+                element
+            })
+            .collect();
+        let total: f32 = elements
+            .iter()
+            .map(|element| element.percent().on_axis(way))
+            .sum();
+        // NOTE: the elements without share need some space left
+        let starved = missing > 0 && rest <= 0.;
+        if starved || rest < 0. || (total - 1.).abs() > SHARE_TOLERANCE {
+            // NOTE: it points at the `[` of the container
+            return Err(Error::ParseLayout {
+                column: start,
+                message: "the shares of the container are not the whole",
+            });
+        }
+        let size_pos = SizeAndPos {
+            size: Size::zero(),
+            position: Position::zero(),
+        };
+        Ok(match way {
+            InsertWay::Horizontal => Element::Horizontal {
+                elements,
+                size_pos,
+                percent: Size::whole(),
+                pinned,
+            },
+            InsertWay::Vertical => Element::Vertical {
+                elements,
+                size_pos,
+                percent: Size::whole(),
+                pinned,
+            },
+        })
+    }
+}
@@ -4,4 +4,21 @@ pub enum FlyjaError {
     ElementNotFound,
     #[error("This drag is illegal, size be minus")]
     DragIllegal,
+    #[error("The window is not in a container")]
+    ContainerNotFound,
+    #[error("The automatic layout is not in use")]
+    LayoutNotActive,
+    #[error("The windows are placed by the automatic layout, the tree cannot be edited")]
+    TreeNotEditable,
+    #[error("The id is already in the map")]
+    DuplicateId,
+    #[error("The layout is invalid: {0}")]
+    InvalidLayout(&'static str),
+    #[error("The layout text is wrong at column {column}: {message}")]
+    ParseLayout {
+        column: usize,
+        message: &'static str,
+    },
+    #[error("The version {0} of the saved layout is not supported")]
+    UnsupportedVersion(u32),
 }
@@ -0,0 +1,244 @@
+use crate::layout::Placement;
+use crate::utils::{MapUnit, MinusAbleMatUnit};
+use crate::{DispatchCallback, Element, Id, InsertWay, Result, SizeAndPos, TopElementMap};
+
+/// What is changed in the map by an operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutEvent<T = f32> {
+    /// A window is put in the map, it is not `visible` when it is put behind the focused window in
+    /// the monocle mode
+    Placed {
+        id: Id,
+        new: SizeAndPos<T>,
+        visible: bool,
+    },
+    /// A window is moved, its size is the same
+    Moved {
+        id: Id,
+        old: SizeAndPos<T>,
+        new: SizeAndPos<T>,
+    },
+    /// The size of a window is changed, it may be moved too
+    Resized {
+        id: Id,
+        old: SizeAndPos<T>,
+        new: SizeAndPos<T>,
+    },
+    /// A window can not be seen anymore, like the ones behind the focused window in the monocle
+    /// mode
+    Hidden {
+        id: Id,
+        old: SizeAndPos<T>,
+        new: SizeAndPos<T>,
+    },
+    /// A hidden window can be seen again
+    Shown {
+        id: Id,
+        old: SizeAndPos<T>,
+        new: SizeAndPos<T>,
+    },
+    /// A window is taken out of the map
+    Removed { id: Id, old: SizeAndPos<T> },
+    /// The focus is moved to another window
+    FocusChanged { old: Option<Id>, new: Option<Id> },
+    /// A container is made, dropped, or its way, size or position is changed. The `path` is the
+    /// indexes of the children from the root to it.
+    ContainerChanged {
+        path: Vec<usize>,
+        old: Option<(InsertWay, SizeAndPos<T>)>,
+        new: Option<(InsertWay, SizeAndPos<T>)>,
+    },
+}
+
+/// It is told about every [`LayoutEvent`] of an operation run by [`TopElementMap::observe`]
+pub trait LayoutObserver<T: MapUnit> {
+    fn notify(&mut self, event: LayoutEvent<T>);
+}
+
+impl<F, T: MapUnit> LayoutObserver<T> for F
+where
+    F: FnMut(LayoutEvent<T>),
+{
+    fn notify(&mut self, event: LayoutEvent<T>) {
+        self(event)
+    }
+}
+
+impl<T: MapUnit> LayoutObserver<T> for () {
+    fn notify(&mut self, _event: LayoutEvent<T>) {}
+}
+
+/// Use a [`DispatchCallback`] as an observer. It gets the new size and position of the windows
+/// which are placed, moved, resized, hidden or shown, like the operations dispatch them.
+pub struct Dispatch<F>(pub F);
+
+impl<T: MapUnit, F: DispatchCallback<T>> LayoutObserver<T> for Dispatch<F> {
+    fn notify(&mut self, event: LayoutEvent<T>) {
+        match event {
+            LayoutEvent::Placed { id, new, .. }
+            | LayoutEvent::Moved { id, new, .. }
+            | LayoutEvent::Resized { id, new, .. }
+            | LayoutEvent::Hidden { id, new, .. }
+            | LayoutEvent::Shown { id, new, .. } => self.0.callback(id, new),
+            _ => {}
+        }
+    }
+}
+
+/// What the observer can see of the map
+struct Scene<T: MapUnit> {
+    windows: Vec<Placement<T>>,
+    containers: Vec<(Vec<usize>, InsertWay, SizeAndPos<T>)>,
+    focused: Option<Id>,
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// Run the operations in `op`, and tell the `observer` what is changed by them when it is
+    /// done. The operations in `op` should be given `&mut ()`.
+    ///
+    /// The windows are compared by their id, and the containers by their place in the tree. The
+    /// changes are told even when `op` fails, as a failed operation may have changed the map.
+    pub fn observe<O, R, P>(&mut self, observer: &mut O, op: P) -> Result<R>
+    where
+        O: LayoutObserver<T>,
+        P: FnOnce(&mut Self) -> Result<R>,
+    {
+        let before = self.scene();
+        let result = op(self);
+        notify_changed(before, self.scene(), observer);
+        result
+    }
+
+    fn scene(&self) -> Scene<T> {
+        let windows = self
+            .placements()
+            .into_iter()
+            .filter(|placement| {
+                !self
+                    .placeholders
+                    .iter()
+                    .any(|placeholder| placeholder.slot == placement.id)
+            })
+            .collect();
+        let mut containers = Vec::new();
+        // NOTE: the tree of a layout which only places the windows is not what is seen
+        if !self.auto.as_ref().is_some_and(|auto| auto.is_placed()) {
+            collect_containers(&self.root, &mut Vec::new(), &mut containers);
+        }
+        Scene {
+            windows,
+            containers,
+            focused: self.focused,
+        }
+    }
+}
+
+fn collect_containers<T: MinusAbleMatUnit>(
+    element: &Element<T>,
+    path: &mut Vec<usize>,
+    containers: &mut Vec<(Vec<usize>, InsertWay, SizeAndPos<T>)>,
+) {
+    let (Element::Vertical { elements, .. } | Element::Horizontal { elements, .. }) = element
+    else {
+        return;
+    };
+    containers.push((
+        path.clone(),
+        element.way().unwrap_or_default(),
+        element.size_pos(),
+    ));
+    for (index, child) in elements.iter().enumerate() {
+        path.push(index);
+        collect_containers(child, path, containers);
+        path.pop();
+    }
+}
+
+fn notify_changed<T, O>(before: Scene<T>, after: Scene<T>, observer: &mut O)
+where
+    T: MapUnit,
+    O: LayoutObserver<T>,
+{
+    for old in &before.windows {
+        if !after.windows.iter().any(|new| new.id == old.id) {
+            observer.notify(LayoutEvent::Removed {
+                id: old.id,
+                old: old.size_pos,
+            });
+        }
+    }
+    for new in &after.windows {
+        let (id, new_size_pos) = (new.id, new.size_pos);
+        let Some(old) = before.windows.iter().find(|old| old.id == id) else {
+            observer.notify(LayoutEvent::Placed {
+                id,
+                new: new_size_pos,
+                visible: new.visible,
+            });
+            continue;
+        };
+        let old_size_pos = old.size_pos;
+        let event = if old.visible && !new.visible {
+            LayoutEvent::Hidden {
+                id,
+                old: old_size_pos,
+                new: new_size_pos,
+            }
+        } else if !old.visible && new.visible {
+            LayoutEvent::Shown {
+                id,
+                old: old_size_pos,
+                new: new_size_pos,
+            }
+        } else if old_size_pos.size != new_size_pos.size {
+            LayoutEvent::Resized {
+                id,
+                old: old_size_pos,
+                new: new_size_pos,
+            }
+        } else if old_size_pos.position != new_size_pos.position {
+            LayoutEvent::Moved {
+                id,
+                old: old_size_pos,
+                new: new_size_pos,
+            }
+        } else {
+            continue;
+        };
+        observer.notify(event);
+    }
+
+    let find = |containers: &[(Vec<usize>, InsertWay, SizeAndPos<T>)], path: &[usize]| {
+        containers
+            .iter()
+            .find(|(o_path, ..)| o_path == path)
+            .map(|(_, way, size_pos)| (*way, *size_pos))
+    };
+    for (path, ..) in &before.containers {
+        if find(&after.containers, path).is_none() {
+            observer.notify(LayoutEvent::ContainerChanged {
+                path: path.clone(),
+                old: find(&before.containers, path),
+                new: None,
+            });
+        }
+    }
+    for (path, way, size_pos) in &after.containers {
+        let old = find(&before.containers, path);
+        let new = Some((*way, *size_pos));
+        if old != new {
+            observer.notify(LayoutEvent::ContainerChanged {
+                path: path.clone(),
+                old,
+                new,
+            });
+        }
+    }
+
+    if before.focused != after.focused {
+        observer.notify(LayoutEvent::FocusChanged {
+            old: before.focused,
+            new: after.focused,
+        });
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::VecDeque;
+
+use crate::layout::AutoState;
+use crate::utils::{MapUnit, MinusAbleMatUnit};
+use crate::{
+    DispatchCallback, Element, Id, Placeholder, Result, SizeAndPos, TopElementMap, dispatch_changed,
+};
+
+/// Everything of the map which is brought back by undo and redo
+#[derive(Debug, Clone)]
+pub(crate) struct State<T: MapUnit> {
+    root: Element<T>,
+    auto: Option<AutoState<T>>,
+    recent: Option<Id>,
+    focused: Option<Id>,
+    monocle: bool,
+    placeholders: Vec<Placeholder>,
+}
+
+/// The undo and redo stacks of a map
+#[derive(Debug, Clone)]
+pub(crate) struct History<T: MapUnit> {
+    limit: usize,
+    undo: VecDeque<State<T>>,
+    redo: Vec<State<T>>,
+    /// How many groups are open, the operations in them are one undo step
+    depth: usize,
+    /// The state before the outer group
+    start: Option<State<T>>,
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// Record the `insert`, `delete`, `swap`, `drag_resize`, `drag_and_drop`, `remap` and
+    /// `set_layout`, so they can be undone. At most `limit` steps are kept, the oldest ones are
+    /// dropped. With [`None`] the history is turned off and dropped.
+    pub fn set_history(&mut self, limit: Option<usize>) {
+        let Some(limit) = limit else {
+            self.history = None;
+            return;
+        };
+        let history = self.history.get_or_insert_with(|| History {
+            limit,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            depth: 0,
+            start: None,
+        });
+        history.limit = limit;
+        while history.undo.len() > limit {
+            history.undo.pop_front();
+        }
+    }
+
+    /// Check if there is a step to undo
+    pub fn can_undo(&self) -> bool {
+        self.history
+            .as_ref()
+            .is_some_and(|history| !history.undo.is_empty())
+    }
+
+    /// Check if there is a step to redo
+    pub fn can_redo(&self) -> bool {
+        self.history
+            .as_ref()
+            .is_some_and(|history| !history.redo.is_empty())
+    }
+
+    /// Start a group, the operations until [`TopElementMap::end_group`] are undone in one step.
+    /// The groups can be nested, only the outer one is a step.
+    pub fn begin_group(&mut self) {
+        let outer = self
+            .history
+            .as_ref()
+            .is_some_and(|history| history.depth == 0);
+        let state = outer.then(|| self.state());
+        let Some(history) = &mut self.history else {
+            return;
+        };
+        if state.is_some() {
+            history.start = state;
+        }
+        history.depth += 1;
+    }
+
+    /// End the group started by [`TopElementMap::begin_group`]
+    pub fn end_group(&mut self) {
+        self.close_group(true);
+    }
+
+    /// Bring the map back to the state before the last step. Every window whose size or
+    /// position changes is dispatched. It returns `false` when there is nothing to undo, or a
+    /// group is open.
+    pub fn undo<F>(&mut self, f: &mut F) -> bool
+    where
+        F: DispatchCallback<T>,
+    {
+        let current = self.state();
+        let Some(history) = &mut self.history else {
+            return false;
+        };
+        if history.depth > 0 {
+            return false;
+        }
+        let Some(state) = history.undo.pop_back() else {
+            return false;
+        };
+        history.redo.push(current);
+        self.restore(state, f);
+        true
+    }
+
+    /// Do the last undone step again. Every window whose size or position changes is
+    /// dispatched. It returns `false` when there is nothing to redo, or a group is open.
+    pub fn redo<F>(&mut self, f: &mut F) -> bool
+    where
+        F: DispatchCallback<T>,
+    {
+        let current = self.state();
+        let Some(history) = &mut self.history else {
+            return false;
+        };
+        if history.depth > 0 {
+            return false;
+        }
+        let Some(state) = history.redo.pop() else {
+            return false;
+        };
+        history.undo.push_back(current);
+        self.restore(state, f);
+        true
+    }
+
+    /// Run an operation as one undo step, it is not recorded when it fails out of a group
+    pub(crate) fn record<R>(&mut self, op: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+        self.begin_group();
+        let result = op(self);
+        self.close_group(result.is_ok());
+        result
+    }
+
+    fn close_group(&mut self, keep: bool) {
+        let Some(history) = &mut self.history else {
+            return;
+        };
+        if history.depth == 0 {
+            return;
+        }
+        history.depth -= 1;
+        if history.depth > 0 {
+            return;
+        }
+        let Some(start) = history.start.take() else {
+            return;
+        };
+        if keep {
+            history.undo.push_back(start);
+            if history.undo.len() > history.limit {
+                history.undo.pop_front();
+            }
+            history.redo.clear();
+        }
+    }
+
+    /// A copy of the map with the history turned off, the steps are not cloned
+    pub(crate) fn clone_without_history(&self) -> Self {
+        TopElementMap {
+            root: self.root.clone(),
+            auto_normalize: self.auto_normalize,
+            auto: self.auto.clone(),
+            insert_policy: self.insert_policy,
+            autotiling: self.autotiling,
+            recent: self.recent,
+            focused: self.focused,
+            monocle: self.monocle,
+            placeholders: self.placeholders.clone(),
+            history: None,
+        }
+    }
+
+    pub(crate) fn state(&self) -> State<T> {
+        State {
+            root: self.root.clone(),
+            auto: self.auto.clone(),
+            recent: self.recent,
+            focused: self.focused,
+            monocle: self.monocle,
+            placeholders: self.placeholders.clone(),
+        }
+    }
+
+    fn restore<F>(&mut self, state: State<T>, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let before = self.visible_geometry();
+        self.reset(state);
+        dispatch_changed(&before, &self.visible_geometry(), f);
+    }
+
+    /// Put the state back without dispatching
+    pub(crate) fn reset(&mut self, state: State<T>) {
+        self.root = state.root;
+        self.auto = state.auto;
+        self.recent = state.recent;
+        self.focused = state.focused;
+        self.monocle = state.monocle;
+        self.placeholders = state.placeholders;
+    }
+
+    /// Where the windows are seen, without the placeholders
+    pub(crate) fn visible_geometry(&self) -> Vec<(Id, SizeAndPos<T>)> {
+        self.placements()
+            .into_iter()
+            .filter(|placement| {
+                !self
+                    .placeholders
+                    .iter()
+                    .any(|placeholder| placeholder.slot == placement.id)
+            })
+            .map(|placement| (placement.id, placement.size_pos))
+            .collect()
+    }
+}
@@ -0,0 +1,479 @@
+//! Load the layouts saved by `i3-save-tree`, which are restored by `append_layout` in i3, and
+//! write the map like the `get_tree` of i3 and sway.
+//!
+//! Every window of the layout becomes a [`Placeholder`](crate::Placeholder) whose criteria come
+//! from its `swallows`, so the windows started later take their slots by
+//! [`TopElementMap::swallow`].
+use serde_json::{Value, json};
+
+use crate::layout::{Placement, container, window};
+use crate::utils::{MapUnit, MinusAbleMatUnit};
+use crate::{
+    Criteria, Element, Error, Id, InsertWay, Placeholder, Result, SizeAndPos, TopElementMap,
+};
+
+/// What is in the i3 layout but cannot be kept in the map
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportWarning {
+    /// The layout of a container is not a split, like `tabbed` or `stacked`. It is imported as
+    /// `splith`
+    UnsupportedLayout(String),
+    /// The floating windows are not imported, it holds how many are dropped
+    FloatingNodes(usize),
+    /// A criterion of `swallows` which cannot be matched, it holds the key and the value
+    UnsupportedCriterion { key: String, value: String },
+    /// Only the first criteria of `swallows` is used, it holds how many are dropped
+    ExtraSwallows(usize),
+    /// A window without `swallows`, it is swallowed by any window
+    MissingSwallows,
+    /// The percents of a container do not add up to the whole, so they are scaled
+    PercentScaled,
+    /// A container without windows is dropped
+    EmptyContainer,
+    /// The name of a node is not kept, it holds the name
+    Name(String),
+    /// The marks of a node are not kept, it holds them
+    Marks(Vec<String>),
+    /// The border of a node is not kept, it holds the `border` and the `current_border_width`
+    Border {
+        style: Option<String>,
+        width: Option<i64>,
+    },
+    /// A fullscreen node is imported as a tiled one
+    Fullscreen,
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// Build a map from a layout saved by `i3-save-tree`. The comments and the many top
+    /// containers of the file are accepted, the top containers are put side by side like in a
+    /// workspace of i3.
+    ///
+    /// Every window gets an id from `ids`, and becomes a placeholder waiting for its window. What
+    /// cannot be imported comes back as warnings.
+    /// It fails when the text is not JSON or the layout has no windows
+    pub fn from_i3_layout<I>(
+        layout: &str,
+        size_pos: SizeAndPos<T>,
+        mut ids: I,
+    ) -> Result<(Self, Vec<ImportWarning>)>
+    where
+        I: FnMut() -> Id,
+    {
+        let json: String = layout
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("//"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let nodes = serde_json::Deserializer::from_str(&json)
+            .into_iter::<Value>()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::InvalidLayout("the i3 layout is not JSON"))?;
+
+        let mut import = Import {
+            ids: &mut ids,
+            slots: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let Some(mut root) = import.container(InsertWay::Horizontal, &nodes) else {
+            return Err(Error::InvalidLayout("the i3 layout has no windows"));
+        };
+        root.remap(size_pos, &mut ());
+        let mut map = TopElementMap::new(size_pos);
+        map.root = root;
+        for (slot, criteria) in import.slots {
+            map.add_placeholder(slot, criteria, None)?;
+        }
+        Ok((map, import.warnings))
+    }
+
+    /// Write the map like the `get_tree` of i3 and sway, so the tools reading it work with the
+    /// map. The map is the only workspace of the only output.
+    ///
+    /// The windows use their ids as the node ids and the `window`, the other nodes get the ids
+    /// after the biggest window id. The placeholders are written like i3 does, with a `null`
+    /// window and their criteria in `swallows`. The `rect` of a window is where it is placed, so
+    /// the automatic layouts and the monocle mode are written as they are seen.
+    pub fn to_i3_tree(&self, output: &str, workspace: &str) -> Value {
+        let placements = self.placements();
+        let mut export = Export {
+            next_id: placements
+                .iter()
+                .map(|placement| placement.id.0 + 1)
+                .max()
+                .unwrap_or(1),
+            placements: &placements,
+            placeholders: &self.placeholders,
+            focused: self.focused,
+        };
+        let size_pos = self.root.size_pos();
+        let (layout, nodes) = match &self.root {
+            Element::EmptyOutput(_) => ("splith", Vec::new()),
+            Element::Window { .. } => ("splith", vec![export.node(&self.root, Some(1.))]),
+            Element::Vertical { elements, .. } | Element::Horizontal { elements, .. } => {
+                let way = self.root.way().unwrap_or_default();
+                let nodes = elements
+                    .iter()
+                    .map(|element| export.node(element, Some(element.percent().on_axis(way))))
+                    .collect();
+                (split(way), nodes)
+            }
+        };
+        let workspace = export.con("workspace", workspace, layout, None, size_pos, nodes);
+        let output = export.con("output", output, "output", None, size_pos, vec![workspace]);
+        export.con("root", "root", "splith", None, size_pos, vec![output])
+    }
+}
+
+/// The criteria as the regexes of i3
+fn swallows(criteria: &Criteria) -> Value {
+    let exact = |value: &str| format!("^{}$", escape(value));
+    let mut swallows = serde_json::Map::new();
+    if let Some(app_id) = &criteria.app_id {
+        swallows.insert("app_id".to_string(), json!(exact(app_id)));
+    }
+    if let Some(class) = &criteria.class {
+        swallows.insert("class".to_string(), json!(exact(class)));
+    }
+    if let Some(title) = &criteria.title {
+        let pattern: String = title
+            .chars()
+            .map(|c| match c {
+                '*' => ".*".to_string(),
+                '?' => ".".to_string(),
+                c => escape(&c.to_string()),
+            })
+            .collect();
+        swallows.insert("title".to_string(), json!(format!("^{pattern}$")));
+    }
+    Value::Object(swallows)
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| {
+            let special = "\\.+*?()|[]{}^$".contains(c);
+            special.then_some('\\').into_iter().chain([c])
+        })
+        .collect()
+}
+
+fn split(way: InsertWay) -> &'static str {
+    match way {
+        InsertWay::Horizontal => "splith",
+        InsertWay::Vertical => "splitv",
+    }
+}
+
+fn rect<T: MapUnit>(size_pos: SizeAndPos<T>) -> Value {
+    json!({
+        "x": size_pos.position.x.to_f32().round() as i64,
+        "y": size_pos.position.y.to_f32().round() as i64,
+        "width": size_pos.size.width.to_f32().round() as i64,
+        "height": size_pos.size.height.to_f32().round() as i64,
+    })
+}
+
+struct Export<'a, T> {
+    next_id: u64,
+    placements: &'a [Placement<T>],
+    placeholders: &'a [Placeholder],
+    focused: Option<Id>,
+}
+
+impl<T: MinusAbleMatUnit> Export<'_, T> {
+    fn node(&mut self, element: &Element<T>, percent: Option<f32>) -> Value {
+        match element {
+            Element::EmptyOutput(size_pos) => {
+                self.con("con", "", "splith", percent, *size_pos, Vec::new())
+            }
+            Element::Window { id, size_pos, .. } => {
+                let placement = self.placements.iter().find(|placement| placement.id == *id);
+                let size_pos = placement.map_or(*size_pos, |placement| placement.size_pos);
+                let visible = placement.is_none_or(|placement| placement.visible);
+                let rect = rect(size_pos);
+                let placeholder = self
+                    .placeholders
+                    .iter()
+                    .find(|placeholder| placeholder.slot == *id);
+                let (window, swallows) = match placeholder {
+                    Some(placeholder) => (Value::Null, json!([swallows(&placeholder.criteria)])),
+                    None => (json!(id.0), json!([])),
+                };
+                json!({
+                    "id": id.0,
+                    "type": "con",
+                    "name": Value::Null,
+                    "layout": "none",
+                    "orientation": "none",
+                    "percent": percent,
+                    "rect": rect,
+                    "window_rect": { "x": 0, "y": 0, "width": rect["width"], "height": rect["height"] },
+                    "deco_rect": { "x": 0, "y": 0, "width": 0, "height": 0 },
+                    "geometry": rect,
+                    "border": "none",
+                    "urgent": false,
+                    "sticky": false,
+                    "focused": self.focused == Some(*id),
+                    "visible": visible,
+                    "fullscreen_mode": 0,
+                    "window": window,
+                    "swallows": swallows,
+                    "focus": [],
+                    "nodes": [],
+                    "floating_nodes": [],
+                })
+            }
+            Element::Vertical { elements, .. } | Element::Horizontal { elements, .. } => {
+                let way = element.way().unwrap_or_default();
+                let nodes = elements
+                    .iter()
+                    .map(|child| self.node(child, Some(child.percent().on_axis(way))))
+                    .collect();
+                self.con("con", "", split(way), percent, element.size_pos(), nodes)
+            }
+        }
+    }
+
+    /// A node which is not a window
+    fn con(
+        &mut self,
+        kind: &str,
+        name: &str,
+        layout: &str,
+        percent: Option<f32>,
+        size_pos: SizeAndPos<T>,
+        nodes: Vec<Value>,
+    ) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        let orientation = match layout {
+            "splith" => "horizontal",
+            "splitv" => "vertical",
+            _ => "none",
+        };
+        // NOTE: the child holding the focused window comes first in the focus stack
+        let mut focus: Vec<&Value> = nodes.iter().collect();
+        focus.sort_by_key(|node| !holds_focus(node));
+        let focus: Vec<Value> = focus.into_iter().map(|node| node["id"].clone()).collect();
+        json!({
+            "id": id,
+            "type": kind,
+            "name": name,
+            "layout": layout,
+            "orientation": orientation,
+            "percent": percent,
+            "rect": rect(size_pos),
+            "window_rect": { "x": 0, "y": 0, "width": 0, "height": 0 },
+            "deco_rect": { "x": 0, "y": 0, "width": 0, "height": 0 },
+            "geometry": { "x": 0, "y": 0, "width": 0, "height": 0 },
+            "border": "none",
+            "urgent": false,
+            "sticky": false,
+            "focused": false,
+            "fullscreen_mode": 0,
+            "window": Value::Null,
+            "focus": focus,
+            "nodes": nodes,
+            "floating_nodes": [],
+        })
+    }
+}
+
+fn holds_focus(node: &Value) -> bool {
+    node["focused"] == true
+        || node["nodes"]
+            .as_array()
+            .is_some_and(|nodes| nodes.iter().any(holds_focus))
+}
+
+struct Import<'a, I> {
+    ids: &'a mut I,
+    slots: Vec<(Id, Criteria)>,
+    warnings: Vec<ImportWarning>,
+}
+
+impl<I: FnMut() -> Id> Import<'_, I> {
+    fn node<T: MinusAbleMatUnit>(&mut self, node: &Value) -> Option<Element<T>> {
+        if let Some(floating) = node["floating_nodes"].as_array()
+            && !floating.is_empty()
+        {
+            self.warnings
+                .push(ImportWarning::FloatingNodes(floating.len()));
+        }
+        self.ignored(node);
+        match node["nodes"].as_array() {
+            Some(nodes) if !nodes.is_empty() => {
+                let way = match node["layout"].as_str() {
+                    Some("splitv") => InsertWay::Vertical,
+                    Some("splith") | None => InsertWay::Horizontal,
+                    Some(layout) => {
+                        self.warnings
+                            .push(ImportWarning::UnsupportedLayout(layout.to_string()));
+                        InsertWay::Horizontal
+                    }
+                };
+                self.container(way, nodes)
+            }
+            // NOTE: a container saved without its windows is empty
+            _ if node["swallows"].is_null()
+                && (node["type"] == "workspace" || node["layout"].is_string()) =>
+            {
+                self.warnings.push(ImportWarning::EmptyContainer);
+                None
+            }
+            _ => {
+                let id = (self.ids)();
+                let criteria = self.criteria(&node["swallows"]);
+                self.slots.push((id, criteria));
+                Some(window(id))
+            }
+        }
+    }
+
+    /// Warn about the keys of the node which are not imported, when they are not the default
+    fn ignored(&mut self, node: &Value) {
+        if let Some(name) = node["name"].as_str()
+            && !name.is_empty()
+        {
+            self.warnings.push(ImportWarning::Name(name.to_string()));
+        }
+        if let Some(marks) = node["marks"].as_array()
+            && !marks.is_empty()
+        {
+            let marks = marks
+                .iter()
+                .map(|mark| {
+                    mark.as_str()
+                        .map_or_else(|| mark.to_string(), str::to_string)
+                })
+                .collect();
+            self.warnings.push(ImportWarning::Marks(marks));
+        }
+        let style = node["border"]
+            .as_str()
+            .filter(|style| *style != "normal")
+            .map(str::to_string);
+        let width = node["current_border_width"]
+            .as_i64()
+            .filter(|width| *width != -1);
+        if style.is_some() || width.is_some() {
+            self.warnings.push(ImportWarning::Border { style, width });
+        }
+        if node["fullscreen_mode"]
+            .as_i64()
+            .is_some_and(|mode| mode != 0)
+        {
+            self.warnings.push(ImportWarning::Fullscreen);
+        }
+    }
+
+    fn container<T: MinusAbleMatUnit>(
+        &mut self,
+        way: InsertWay,
+        nodes: &[Value],
+    ) -> Option<Element<T>> {
+        let children: Vec<(Element<T>, Option<f64>)> = nodes
+            .iter()
+            .filter_map(|node| Some((self.node(node)?, node["percent"].as_f64())))
+            .collect();
+        if children.is_empty() {
+            return None;
+        }
+        // NOTE: the children without percent share the space left
+        let given: f64 = children.iter().filter_map(|(_, percent)| *percent).sum();
+        let missing = children
+            .iter()
+            .filter(|(_, percent)| percent.is_none())
+            .count();
+        let rest = if missing == 0 {
+            0.
+        } else {
+            (1. - given).max(0.) / missing as f64
+        };
+        let mut shares: Vec<f64> = children
+            .iter()
+            .map(|(_, percent)| percent.unwrap_or(rest))
+            .collect();
+        let total: f64 = shares.iter().sum();
+        if total <= 0. {
+            let even = 1. / shares.len() as f64;
+            shares.fill(even);
+        } else if (total - 1.).abs() > 1e-3 {
+            self.warnings.push(ImportWarning::PercentScaled);
+            shares.iter_mut().for_each(|share| *share /= total);
+        }
+        Some(container(
+            way,
+            children
+                .into_iter()
+                .zip(shares)
+                .map(|((element, _), share)| (element, share as f32))
+                .collect(),
+        ))
+    }
+
+    fn criteria(&mut self, swallows: &Value) -> Criteria {
+        let Some((first, rest)) = swallows
+            .as_array()
+            .and_then(|swallows| swallows.split_first())
+        else {
+            self.warnings.push(ImportWarning::MissingSwallows);
+            return Criteria::default();
+        };
+        if !rest.is_empty() {
+            self.warnings.push(ImportWarning::ExtraSwallows(rest.len()));
+        }
+        let mut criteria = Criteria::default();
+        for (key, value) in first.as_object().into_iter().flatten() {
+            let value = value.as_str().unwrap_or_default();
+            let converted = match key.as_str() {
+                "app_id" => exact(value).map(|value| criteria.app_id = Some(value)),
+                "class" => exact(value).map(|value| criteria.class = Some(value)),
+                "title" => pattern(value).map(|value| criteria.title = Some(value)),
+                _ => None,
+            };
+            if converted.is_none() {
+                self.warnings.push(ImportWarning::UnsupportedCriterion {
+                    key: key.clone(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        criteria
+    }
+}
+
+/// Turn a regex of i3 to a title pattern of [`Criteria`], `.*` becomes `*` and `.` becomes `?`
+fn pattern(regex: &str) -> Option<String> {
+    let (start, regex) = match regex.strip_prefix('^') {
+        Some(regex) => ("", regex),
+        None => ("*", regex),
+    };
+    let (regex, end) = match regex.strip_suffix('$') {
+        Some(regex) => (regex, ""),
+        None => (regex, "*"),
+    };
+    let mut pattern = start.to_string();
+    let mut chars = regex.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => pattern.push(chars.next()?),
+            '.' if chars.peek() == Some(&'*') => {
+                chars.next();
+                pattern.push('*');
+            }
+            '.' => pattern.push('?'),
+            '(' | ')' | '[' | ']' | '{' | '}' | '|' | '+' | '?' | '*' | '^' | '$' => return None,
+            c => pattern.push(c),
+        }
+    }
+    pattern.push_str(end);
+    // NOTE: the literal `*` and `?` cannot be told from the wildcards
+    (!regex.contains("\\*") && !regex.contains("\\?")).then_some(pattern)
+}
+
+/// Turn a regex of i3 to an exact value, only `^value$` can be
+fn exact(regex: &str) -> Option<String> {
+    let pattern = pattern(regex)?;
+    (!pattern.contains(['*', '?'])).then_some(pattern)
+}
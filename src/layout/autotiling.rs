@@ -0,0 +1,76 @@
+use crate::utils::{MapUnit, MinusAbleMatUnit};
+use crate::{DispatchCallback, Error, Id, InsertWay, Result, Size, SizeAndPos, TopElementMap};
+
+/// Split a window along its longer side, like the autotiling script of sway.
+///
+/// The side is measured on what can be seen of the window, so the gaps around it and its
+/// decorations are taken off first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Autotiling<T = f32> {
+    /// The gaps on each side of a window, the `width` is the gap on the left and the right
+    pub gaps: Size<T>,
+    /// The border on each side of a window
+    pub border: T,
+    /// The height of the title bar
+    pub title: T,
+}
+
+impl<T: MapUnit> Default for Autotiling<T> {
+    fn default() -> Self {
+        Self {
+            gaps: Size {
+                width: T::zero(),
+                height: T::zero(),
+            },
+            border: T::zero(),
+            title: T::zero(),
+        }
+    }
+}
+
+impl<T: MapUnit> Autotiling<T> {
+    /// The way to split the `target` along its longer side
+    pub fn way(&self, target: SizeAndPos<T>) -> InsertWay {
+        // NOTE: the space taken on one axis is added to the other one, so unsigned units do not
+        // go under zero
+        let taken_x = (self.gaps.width + self.border) + (self.gaps.width + self.border);
+        let taken_y =
+            (self.gaps.height + self.border) + (self.gaps.height + self.border) + self.title;
+        if target.size.width + taken_y >= target.size.height + taken_x {
+            InsertWay::Horizontal
+        } else {
+            InsertWay::Vertical
+        }
+    }
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// How the map splits the windows by itself, [`None`] means it is not turned on
+    pub fn autotiling(&self) -> Option<Autotiling<T>> {
+        self.autotiling
+    }
+
+    /// Turn on the autotiling, then [`TopElementMap::push`] splits the last window along its
+    /// longer side instead of the default way
+    pub fn set_autotiling(&mut self, autotiling: Option<Autotiling<T>>) {
+        self.autotiling = autotiling;
+    }
+
+    /// Insert a window beside the target, and split the target along its longer side. It uses the
+    /// autotiling of the map, or no gaps and decorations when it is not turned on
+    /// It only fails when the target id is not found
+    pub fn insert_auto<F>(&mut self, id: Id, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let Some(target_element) = self.root.find_window(target) else {
+            return Err(Error::ElementNotFound);
+        };
+        let way = self
+            .autotiling
+            .unwrap_or_default()
+            .way(target_element.size_pos());
+        self.insert_new(id, target, way, f)
+    }
+}
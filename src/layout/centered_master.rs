@@ -0,0 +1,85 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{Id, Position, Size, SizeAndPos};
+
+use super::{LayoutPolicy, Placement};
+
+/// The master window is in a column in the center, the other windows are put in the left and
+/// right stacks in turn, the first one goes to the left. It is made for ultrawide monitors.
+///
+/// The master column keeps its width even without stacks, so the space on both sides is left
+/// empty. That cannot be a tree, so the windows are placed by [`CenteredMaster::arrange`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CenteredMaster {
+    /// How much width the master column takes, between 0 and 1
+    pub ratio: f32,
+}
+
+impl Default for CenteredMaster {
+    fn default() -> Self {
+        Self { ratio: 0.5 }
+    }
+}
+
+impl CenteredMaster {
+    /// Place the windows in the `size_pos`, the first window is the master
+    pub fn arrange<T: MinusAbleMatUnit>(
+        &self,
+        windows: &[Id],
+        size_pos: SizeAndPos<T>,
+    ) -> Vec<Placement<T>> {
+        let Some((master, stack)) = windows.split_first() else {
+            return Vec::new();
+        };
+        let SizeAndPos { size, position } = size_pos;
+        let master_width = size.width.mul_f32(self.ratio.clamp(0., 1.));
+        let side_width = (size.width - master_width) / T::two();
+
+        let mut placements = vec![Placement {
+            id: *master,
+            size_pos: SizeAndPos {
+                size: Size {
+                    width: master_width,
+                    height: size.height,
+                },
+                position: Position {
+                    x: position.x + side_width,
+                    y: position.y,
+                },
+            },
+            visible: true,
+        }];
+        let left: Vec<Id> = stack.iter().step_by(2).copied().collect();
+        let right: Vec<Id> = stack.iter().skip(1).step_by(2).copied().collect();
+        for (column, x) in [
+            (left, position.x),
+            (right, position.x + side_width + master_width),
+        ] {
+            if column.is_empty() {
+                continue;
+            }
+            let height = size.height / T::from_f32(column.len() as f32);
+            let mut y = position.y;
+            for id in column {
+                placements.push(Placement {
+                    id,
+                    size_pos: SizeAndPos {
+                        size: Size {
+                            width: side_width,
+                            height,
+                        },
+                        position: Position { x, y },
+                    },
+                    visible: true,
+                });
+                y += height;
+            }
+        }
+        placements
+    }
+}
+
+impl<T: MinusAbleMatUnit> LayoutPolicy<T> for CenteredMaster {
+    fn arrange(&self, windows: &[Id], size_pos: SizeAndPos<T>) -> Vec<Placement<T>> {
+        CenteredMaster::arrange(self, windows, size_pos)
+    }
+}
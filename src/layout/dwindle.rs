@@ -0,0 +1,69 @@
+use crate::utils::{MapUnit, MinusAbleMatUnit};
+use crate::{Direction, Element, InsertWay, SizeAndPos};
+
+/// Split the most recent window when a new one is pushed, like the dwindle layout of Hyprland
+/// and the spiral layout of bspwm
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dwindle {
+    /// How much space the split window keeps, between 0 and 1
+    pub ratio: f32,
+    /// Turn round in a spiral instead of splitting along the longer side
+    pub spiral: bool,
+    /// Keep the way of every split when the map is remapped, else the way of the splits follows
+    /// their aspect ratio. It is not used in the spiral
+    pub preserve_split: bool,
+}
+
+impl Default for Dwindle {
+    fn default() -> Self {
+        Self {
+            ratio: 0.5,
+            spiral: false,
+            preserve_split: false,
+        }
+    }
+}
+
+impl Dwindle {
+    /// Where the new window is put beside the split window, `count` is how many windows are in
+    /// the map before the new one
+    pub fn direction<T: MapUnit>(&self, target: SizeAndPos<T>, count: usize) -> Direction {
+        if self.spiral {
+            const TURNS: [Direction; 4] = [
+                Direction::Right,
+                Direction::Bottom,
+                Direction::Left,
+                Direction::Top,
+            ];
+            return TURNS[count.saturating_sub(1) % TURNS.len()];
+        }
+        longer_side(target).into()
+    }
+}
+
+/// The way to split along the longer side
+pub(crate) fn longer_side<T: MapUnit>(size_pos: SizeAndPos<T>) -> InsertWay {
+    if size_pos.size.width >= size_pos.size.height {
+        InsertWay::Horizontal
+    } else {
+        InsertWay::Vertical
+    }
+}
+
+impl<T: MinusAbleMatUnit> Element<T> {
+    /// Turn every split of two elements to its longer side, then remap them
+    pub(crate) fn reorient(&mut self) {
+        let (Self::Vertical { elements, .. } | Self::Horizontal { elements, .. }) = self else {
+            return;
+        };
+        if elements.len() == 2 && !self.is_pinned() {
+            self.set_way(longer_side(self.size_pos()), &mut ());
+        }
+        if let Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } = self {
+            for element in elements {
+                element.reorient();
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{Element, Id, InsertWay, SizeAndPos};
+
+use super::policy::tree_placements;
+use super::{LayoutPolicy, Placement, container, even};
+
+/// Place the windows in a grid of rows and columns which is about square. The last row is
+/// stretched when it is not full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Grid {
+    /// How many columns are in a row, [`None`] makes the grid about square
+    pub columns: Option<usize>,
+}
+
+impl Grid {
+    /// How many columns are in a row for `count` windows
+    pub fn columns(&self, count: usize) -> usize {
+        let columns = self
+            .columns
+            .unwrap_or_else(|| (count as f32).sqrt().ceil() as usize);
+        columns.clamp(1, count.max(1))
+    }
+
+    /// Build the tree of the windows in the `size_pos`
+    pub fn build<T: MinusAbleMatUnit>(
+        &self,
+        windows: &[Id],
+        size_pos: SizeAndPos<T>,
+    ) -> Element<T> {
+        if windows.is_empty() {
+            return Element::new(size_pos);
+        }
+        let rows: Vec<&[Id]> = windows.chunks(self.columns(windows.len())).collect();
+        let share = 1. / rows.len() as f32;
+        let mut element = container(
+            InsertWay::Vertical,
+            rows.into_iter()
+                .map(|row| (even(InsertWay::Horizontal, row), share))
+                .collect(),
+        );
+        element.remap(size_pos, &mut ());
+        element
+    }
+}
+
+impl<T: MinusAbleMatUnit> LayoutPolicy<T> for Grid {
+    fn arrange(&self, windows: &[Id], size_pos: SizeAndPos<T>) -> Vec<Placement<T>> {
+        tree_placements(&Grid::build(self, windows, size_pos))
+    }
+
+    fn build(&self, windows: &[Id], size_pos: SizeAndPos<T>) -> Option<Element<T>> {
+        Some(Grid::build(self, windows, size_pos))
+    }
+}
@@ -0,0 +1,116 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{Direction, Element, Id, InsertWay, Result, SizeAndPos};
+
+use super::policy::tree_placements;
+use super::{LayoutPolicy, Placement, container, even};
+
+/// The master-stack layout of dwm and xmonad.
+///
+/// The first `count` windows are placed in the master area, which takes `ratio` of the map on
+/// the `side`. The other windows are stacked in the rest space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasterStack {
+    /// How many windows are in the master area
+    pub count: usize,
+    /// How much space the master area takes, between 0 and 1
+    pub ratio: f32,
+    /// Which side of the map the master area is on
+    pub side: Direction,
+}
+
+impl Default for MasterStack {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            ratio: 0.5,
+            side: Direction::Left,
+        }
+    }
+}
+
+impl MasterStack {
+    /// Build the tree of the windows in the `size_pos`
+    pub fn build<T: MinusAbleMatUnit>(
+        &self,
+        windows: &[Id],
+        size_pos: SizeAndPos<T>,
+    ) -> Element<T> {
+        // NOTE: the master area is beside the stack, and the windows in them are stacked on the
+        // other axis
+        let (outer_way, inner_way) = match self.side {
+            Direction::Left | Direction::Right => (InsertWay::Horizontal, InsertWay::Vertical),
+            Direction::Top | Direction::Bottom => (InsertWay::Vertical, InsertWay::Horizontal),
+        };
+        let count = self.count.min(windows.len());
+        let (masters, stack) = windows.split_at(count);
+        let mut element = match (masters.is_empty(), stack.is_empty()) {
+            (true, true) => Element::new(size_pos),
+            (false, true) => even(inner_way, masters),
+            (true, false) => even(inner_way, stack),
+            (false, false) => {
+                let ratio = self.ratio.clamp(0., 1.);
+                let master = (even(inner_way, masters), ratio);
+                let stack = (even(inner_way, stack), 1. - ratio);
+                let elements = if self.side.is_end() {
+                    vec![stack, master]
+                } else {
+                    vec![master, stack]
+                };
+                container(outer_way, elements)
+            }
+        };
+        element.remap(size_pos, &mut ());
+        element
+    }
+}
+
+impl<T: MinusAbleMatUnit> LayoutPolicy<T> for MasterStack {
+    fn arrange(&self, windows: &[Id], size_pos: SizeAndPos<T>) -> Vec<Placement<T>> {
+        tree_placements(&MasterStack::build(self, windows, size_pos))
+    }
+
+    fn build(&self, windows: &[Id], size_pos: SizeAndPos<T>) -> Option<Element<T>> {
+        Some(MasterStack::build(self, windows, size_pos))
+    }
+
+    /// Dragging the edge between the master area and the stack changes the ratio, so it is kept
+    /// when the windows change
+    fn on_resize(
+        &mut self,
+        windows: &[Id],
+        target: Id,
+        transfer: T,
+        direction: Direction,
+        size_pos: SizeAndPos<T>,
+    ) -> Result<bool> {
+        let count = self.count.min(windows.len());
+        if count == 0 || count == windows.len() {
+            return Ok(false);
+        }
+        let Some(index) = windows.iter().position(|id| *id == target) else {
+            return Ok(false);
+        };
+        let is_master = index < count;
+        if !(is_master && direction == self.side.opposite() || !is_master && direction == self.side)
+        {
+            return Ok(false);
+        }
+        let extent = match self.side {
+            Direction::Left | Direction::Right => size_pos.size.width.to_f32(),
+            Direction::Top | Direction::Bottom => size_pos.size.height.to_f32(),
+        };
+        // NOTE: the edge moves by transfer on the axis, the master area grows when it is on the
+        // start side
+        let change = transfer.to_f32() / extent;
+        let ratio = if self.side.is_end() {
+            self.ratio - change
+        } else {
+            self.ratio + change
+        };
+        if !(0. ..=1.).contains(&ratio) {
+            return Err(crate::Error::DragIllegal);
+        }
+        self.ratio = ratio;
+        Ok(true)
+    }
+}
@@ -0,0 +1,386 @@
+//! Automatic layouts, which place the windows of a map by their order instead of editing the
+//! tree by hand.
+//!
+//! Every automatic layout is a [`LayoutPolicy`]. When the policy builds a tree, the tree is
+//! rebuilt when the windows are added or removed, or the layout is changed. Between them it is an
+//! ordinary tree, so `drag_resize` and `swap` work on it and are kept.
+mod autotiling;
+mod centered_master;
+mod dwindle;
+mod grid;
+mod master_stack;
+mod policy;
+mod scrolling;
+
+pub use autotiling::Autotiling;
+pub use centered_master::CenteredMaster;
+pub use dwindle::Dwindle;
+pub use grid::Grid;
+pub use master_stack::MasterStack;
+pub use policy::{AnyLayoutPolicy, LayoutPolicy};
+pub use scrolling::{Column, Scrolling};
+
+use crate::utils::{MapUnit, MinusAbleMatUnit};
+use crate::{
+    Direction, DispatchCallback, Element, Error, Id, InsertWay, Percentage, Position, Result, Size,
+    SizeAndPos, TopElementMap, dispatch_changed,
+};
+
+/// Where a window is placed by a layout which is not a tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement<T = f32> {
+    pub id: Id,
+    pub size_pos: SizeAndPos<T>,
+    /// If the window can be seen on the output
+    pub visible: bool,
+}
+
+/// How [`TopElementMap::push`] places a new window when the tree is edited by hand
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InsertPolicy {
+    /// Split the last window of the tree
+    #[default]
+    Last,
+    /// Split the most recent window by [`Dwindle`]
+    Dwindle(Dwindle),
+}
+
+/// The automatic layout and the order of windows in it
+#[derive(Debug, Clone)]
+pub(crate) struct AutoState<T: MapUnit> {
+    pub(crate) policy: Box<dyn LayoutPolicy<T>>,
+    pub(crate) windows: Vec<Id>,
+    /// The placements given by the policy, when it does not build a tree
+    pub(crate) placements: Option<Vec<Placement<T>>>,
+    /// The tree edited by hand before the automatic layout, it comes back when the layout is
+    /// turned off
+    pub(crate) manual: Option<Element<T>>,
+}
+
+impl<T: MapUnit> AutoState<T> {
+    pub(crate) fn insert(&mut self, id: Id, target: Option<Id>, end: bool) -> Result<()> {
+        self.policy.on_insert(&mut self.windows, id, target, end)
+    }
+
+    pub(crate) fn delete(&mut self, target: Id) -> Result<()> {
+        self.policy.on_delete(&mut self.windows, target)
+    }
+
+    pub(crate) fn swap(&mut self, id: Id, target: Id) -> Result<()> {
+        let index_one = self.windows.iter().position(|o_id| *o_id == id);
+        let index_two = self.windows.iter().position(|o_id| *o_id == target);
+        let (Some(index_one), Some(index_two)) = (index_one, index_two) else {
+            return Err(Error::ElementNotFound);
+        };
+        self.windows.swap(index_one, index_two);
+        Ok(())
+    }
+
+    pub(crate) fn contains(&self, target: Id) -> bool {
+        self.windows.contains(&target)
+    }
+
+    /// The policy only places the windows, there is no tree to edit
+    pub(crate) fn is_placed(&self) -> bool {
+        self.placements.is_some()
+    }
+}
+
+/// A window which is not placed yet, it will get its size and position after remap
+pub(crate) fn window<T: MapUnit>(id: Id) -> Element<T> {
+    Element::Window {
+        id,
+        size_pos: SizeAndPos {
+            size: Size::zero(),
+            position: Position::zero(),
+        },
+        percent: Size::whole(),
+    }
+}
+
+/// Put the elements in a container, with their share on the `way` axis
+/// A single element is returned as it is
+pub(crate) fn container<T: MinusAbleMatUnit>(
+    way: InsertWay,
+    mut elements: Vec<(Element<T>, f32)>,
+) -> Element<T> {
+    if elements.len() == 1 {
+        let (mut element, _) = elements.remove(0);
+        element.set_percentage(Size::whole());
+        return element;
+    }
+    let elements = elements
+        .into_iter()
+        .map(|(mut element, share)| {
+            element.set_percentage(Percentage::along(way, share));
+            element
+        })
+        .collect();
+    let size_pos = SizeAndPos {
+        size: Size::zero(),
+        position: Position::zero(),
+    };
+    match way {
+        InsertWay::Vertical => Element::Vertical {
+            elements,
+            size_pos,
+            percent: Size::whole(),
+            pinned: false,
+        },
+        InsertWay::Horizontal => Element::Horizontal {
+            elements,
+            size_pos,
+            percent: Size::whole(),
+            pinned: false,
+        },
+    }
+}
+
+/// Put the windows in a container, every one has the same share
+pub(crate) fn even<T: MinusAbleMatUnit>(way: InsertWay, windows: &[Id]) -> Element<T> {
+    let share = 1. / windows.len() as f32;
+    container(way, windows.iter().map(|id| (window(*id), share)).collect())
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// The automatic layout in use, [`None`] means the tree is edited by hand
+    pub fn layout(&self) -> Option<&dyn LayoutPolicy<T>> {
+        self.auto.as_ref().map(|auto| auto.policy.as_ref())
+    }
+
+    /// The windows in the order of the automatic layout
+    pub fn layout_windows(&self) -> Option<&[Id]> {
+        self.auto.as_ref().map(|auto| auto.windows.as_slice())
+    }
+
+    /// Use an automatic layout, or go back to edit the tree by hand with [`None`].
+    ///
+    /// When it is turned on, the windows are ordered by the tree, and the tree is kept, then they
+    /// are placed again. When it is turned off, the kept tree comes back with its percents. The
+    /// windows deleted in the meantime are removed from it, and the new ones are pushed into it
+    /// by the [`InsertPolicy`]. Only the windows which are moved are dispatched.
+    pub fn set_layout<F>(&mut self, layout: Option<Box<dyn LayoutPolicy<T>>>, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let _ = self.record(|map| {
+            let Some(policy) = layout else {
+                if let Some(manual) = map.auto.take().and_then(|auto| auto.manual) {
+                    map.restore_manual(manual, f);
+                }
+                return Ok(());
+            };
+            match &mut map.auto {
+                Some(auto) => auto.policy = policy,
+                None => {
+                    let windows = map.root.windows().into_iter().map(|(id, _)| id).collect();
+                    map.auto = Some(AutoState {
+                        policy,
+                        windows,
+                        placements: None,
+                        manual: Some(map.root.clone()),
+                    });
+                }
+            }
+            map.relayout(f);
+            Ok(())
+        });
+    }
+
+    /// Change the automatic layout in use, then the windows are placed again
+    /// It fails when the map is not in an automatic layout of the type `P`
+    pub fn update_layout<P, U, F>(&mut self, update: U, f: &mut F) -> Result<()>
+    where
+        P: 'static,
+        U: FnOnce(&mut P),
+        F: DispatchCallback<T>,
+    {
+        let Some(policy) = self
+            .auto
+            .as_mut()
+            .and_then(|auto| auto.policy.as_any_mut().downcast_mut::<P>())
+        else {
+            return Err(Error::LayoutNotActive);
+        };
+        update(policy);
+        self.relayout(f);
+        Ok(())
+    }
+
+    /// Set how many windows are in the master area
+    /// It fails when the map is not in the master-stack layout
+    pub fn set_master_count<F>(&mut self, count: usize, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update_layout(
+            |master_stack: &mut MasterStack| master_stack.count = count,
+            f,
+        )
+    }
+
+    /// Set how much space the master area takes
+    /// It fails when the map is not in the master-stack layout
+    pub fn set_master_ratio<F>(&mut self, ratio: f32, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update_layout(
+            |master_stack: &mut MasterStack| master_stack.ratio = ratio,
+            f,
+        )
+    }
+
+    /// Set which side the master area is on
+    /// It fails when the map is not in the master-stack layout
+    pub fn set_master_side<F>(&mut self, side: Direction, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update_layout(|master_stack: &mut MasterStack| master_stack.side = side, f)
+    }
+
+    /// Move the window to the first place of the automatic layout, it becomes the master
+    /// It fails when the map is not in an automatic layout, or the target id is not found
+    pub fn promote<F>(&mut self, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let Some(auto) = &mut self.auto else {
+            return Err(Error::LayoutNotActive);
+        };
+        let Some(index) = auto.windows.iter().position(|id| *id == target) else {
+            return Err(Error::ElementNotFound);
+        };
+        let id = auto.windows.remove(index);
+        auto.windows.insert(0, id);
+        self.relayout(f);
+        Ok(())
+    }
+
+    /// How [`TopElementMap::push`] places a new window when the tree is edited by hand
+    pub fn insert_policy(&self) -> InsertPolicy {
+        self.insert_policy
+    }
+
+    /// Set how [`TopElementMap::push`] places a new window when the tree is edited by hand
+    pub fn set_insert_policy(&mut self, insert_policy: InsertPolicy) {
+        self.insert_policy = insert_policy;
+    }
+
+    /// Split the target by the [`Dwindle`], only the windows which are moved are dispatched
+    pub(crate) fn dwindle_insert<F>(
+        &mut self,
+        id: Id,
+        target: Id,
+        dwindle: Dwindle,
+        f: &mut F,
+    ) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let Some(target_element) = self.root.find_window(target) else {
+            return Err(Error::ElementNotFound);
+        };
+        let direction = dwindle.direction(target_element.size_pos(), self.root.window_count());
+        let before = self.root.windows();
+        self.insert(id, target, direction, &mut ())?;
+        // NOTE: the two windows share the space of the target, give them the ratio
+        if let Some(container) = self.root.find_container_mut(id)
+            && let Some(way) = container.way()
+        {
+            if let Element::Vertical { elements, .. } | Element::Horizontal { elements, .. } =
+                container
+            {
+                let find = |target: Id| elements.iter().position(|e| e.id() == Some(target));
+                if let (Some(index_target), Some(index_new)) = (find(target), find(id)) {
+                    let total = elements[index_target].percent().on_axis(way)
+                        + elements[index_new].percent().on_axis(way);
+                    let ratio = dwindle.ratio.clamp(0., 1.);
+                    elements[index_target].set_percentage(Percentage::along(way, total * ratio));
+                    elements[index_new]
+                        .set_percentage(Percentage::along(way, total * (1. - ratio)));
+                }
+            }
+            container.remap(container.size_pos(), &mut ());
+        }
+        dispatch_changed(&before, &self.root.windows(), f);
+        Ok(())
+    }
+
+    /// Bring the tree edited by hand back, and merge the windows changed in the automatic layout
+    /// into it
+    fn restore_manual<F>(&mut self, manual: Element<T>, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        let before = self.root.windows();
+        let size_pos = self.root.size_pos();
+        self.root = manual;
+        self.root.remap(size_pos, &mut ());
+        for (id, _) in self.root.windows() {
+            if !before.iter().any(|(o_id, _)| *o_id == id) {
+                let _ = self.root.delete(id, &mut ());
+            }
+        }
+        for (id, _) in &before {
+            if self.root.find_window(*id).is_none() {
+                let _ = self.push_window(*id, &mut ());
+            }
+        }
+        self.after_change();
+        dispatch_changed(&before, &self.root.windows(), f);
+    }
+
+    /// Place the windows again with the automatic layout, only the windows which are moved are
+    /// dispatched
+    pub(crate) fn relayout<F>(&mut self, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        let Some(auto) = &mut self.auto else {
+            return;
+        };
+        let before = self.root.windows();
+        let size_pos = self.root.size_pos();
+        match auto.policy.build(&auto.windows, size_pos) {
+            Some(tree) => {
+                self.root = tree;
+                auto.placements = None;
+            }
+            None => {
+                let placements = auto.policy.arrange(&auto.windows, size_pos);
+                // NOTE: the tree only keeps the windows in the map, they are placed by the policy
+                let mut tree = if placements.is_empty() {
+                    Element::new(size_pos)
+                } else {
+                    let ids: Vec<Id> = placements.iter().map(|placement| placement.id).collect();
+                    let mut tree = match even(InsertWay::Horizontal, &ids) {
+                        // NOTE: a lonely window is kept in a container, so the root keeps the size
+                        // of the map when the window is placed somewhere else
+                        window @ Element::Window { .. } => Element::Horizontal {
+                            elements: vec![window],
+                            size_pos,
+                            percent: Size::whole(),
+                            pinned: true,
+                        },
+                        tree => tree,
+                    };
+                    tree.set_size_and_pos(size_pos);
+                    tree
+                };
+                for placement in &placements {
+                    if let Some(window) = tree.find_window_mut(placement.id) {
+                        window.set_size_and_pos(placement.size_pos);
+                    }
+                }
+                self.root = tree;
+                auto.placements = Some(placements);
+            }
+        }
+        dispatch_changed(&before, &self.root.windows(), f);
+    }
+}
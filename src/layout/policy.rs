@@ -0,0 +1,118 @@
+use std::any::Any;
+use std::fmt::Debug;
+
+use crate::utils::MapUnit;
+use crate::{Direction, Element, Error, Id, Result, SizeAndPos};
+
+use super::Placement;
+
+/// An automatic layout, which places the windows of a map by their order.
+///
+/// The map keeps the order of windows, and asks the policy where they are when the windows or the
+/// size of the map change. Implement it in your own crate and attach it by
+/// [`TopElementMap::set_layout`](crate::TopElementMap::set_layout) to write your own layout. The
+/// [`Clone`] and [`Debug`] are needed, so the map can still be cloned and printed.
+pub trait LayoutPolicy<T: MapUnit>: AnyLayoutPolicy<T> + Debug {
+    /// Place the windows in the `size_pos`
+    fn arrange(&self, windows: &[Id], size_pos: SizeAndPos<T>) -> Vec<Placement<T>>;
+
+    /// Build a tree of the windows in the `size_pos`.
+    ///
+    /// When it is given, the map uses it as its tree until the windows change, so `swap` and
+    /// `drag_resize` work on it like a tree edited by hand. Else the map is placed by
+    /// [`LayoutPolicy::arrange`] only.
+    fn build(&self, _windows: &[Id], _size_pos: SizeAndPos<T>) -> Option<Element<T>> {
+        None
+    }
+
+    /// A window is inserted, put it in the order. The `target` is the window it is inserted
+    /// beside, before it or after it by `end`. By default it is put beside the target, or at the
+    /// end without target.
+    fn on_insert(
+        &mut self,
+        windows: &mut Vec<Id>,
+        id: Id,
+        target: Option<Id>,
+        end: bool,
+    ) -> Result<()> {
+        let index = match target {
+            Some(target) if !windows.is_empty() => {
+                let Some(index) = windows.iter().position(|o_id| *o_id == target) else {
+                    return Err(Error::ElementNotFound);
+                };
+                if end { index + 1 } else { index }
+            }
+            _ => windows.len(),
+        };
+        windows.insert(index, id);
+        Ok(())
+    }
+
+    /// A window is deleted, remove it from the order
+    fn on_delete(&mut self, windows: &mut Vec<Id>, target: Id) -> Result<()> {
+        let Some(index) = windows.iter().position(|id| *id == target) else {
+            return Err(Error::ElementNotFound);
+        };
+        windows.remove(index);
+        Ok(())
+    }
+
+    /// A window is resized by drag. Return `true` when the policy takes it, then the windows are
+    /// placed again. With `false` the tree of [`LayoutPolicy::build`] is resized, which is the
+    /// default.
+    fn on_resize(
+        &mut self,
+        _windows: &[Id],
+        _target: Id,
+        _transfer: T,
+        _direction: Direction,
+        _size_pos: SizeAndPos<T>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+/// It is implemented for every [`LayoutPolicy`] which is [`Clone`], so the map can clone the
+/// policy and find it back by its type
+pub trait AnyLayoutPolicy<T: MapUnit> {
+    fn clone_box(&self) -> Box<dyn LayoutPolicy<T>>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: MapUnit, P> AnyLayoutPolicy<T> for P
+where
+    P: LayoutPolicy<T> + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn LayoutPolicy<T>> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T: MapUnit> Clone for Box<dyn LayoutPolicy<T>> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The placements of the windows in the tree, all of them are visible
+pub(crate) fn tree_placements<T>(element: &Element<T>) -> Vec<Placement<T>>
+where
+    T: crate::utils::MinusAbleMatUnit,
+{
+    element
+        .windows()
+        .into_iter()
+        .map(|(id, size_pos)| Placement {
+            id,
+            size_pos,
+            visible: true,
+        })
+        .collect()
+}
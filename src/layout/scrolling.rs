@@ -0,0 +1,419 @@
+use std::fmt::Debug;
+
+use crate::utils::MinusAbleMatUnit;
+use crate::{
+    Direction, DispatchCallback, Error, Id, Position, Result, Size, SizeAndPos, dispatch_changed,
+};
+
+use super::{LayoutPolicy, Placement};
+
+/// A column of the [`Scrolling`] layout, the windows in it are stacked
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    /// The windows from top to bottom
+    pub windows: Vec<Id>,
+    /// The index of the preset width
+    pub preset: usize,
+}
+
+/// An infinite strip of columns like PaperWM and niri.
+///
+/// Every column has a preset width, which is a fraction of the output. The view is scrolled to
+/// keep the focused column on the output, the windows out of it are not visible. The positions
+/// of windows are relative to the scroll offset, so a window on the left of the view has a
+/// position before the output.
+///
+/// It can be used by itself, or as the layout of a map by
+/// [`TopElementMap::set_layout`](crate::TopElementMap::set_layout). In the map a new window
+/// gets a new column, on the right of the focused one by `push`, or beside the target column by
+/// `insert`, and the size of the map is used as the output. Change the focus or the widths in the
+/// map by [`TopElementMap::update_layout`](crate::TopElementMap::update_layout).
+#[derive(Debug, Clone)]
+pub struct Scrolling<T: MinusAbleMatUnit = f32> {
+    output: SizeAndPos<T>,
+    presets: Vec<f32>,
+    default_preset: usize,
+    columns: Vec<Column>,
+    focused: Option<Id>,
+    offset: T,
+}
+
+impl<T: MinusAbleMatUnit> Scrolling<T> {
+    /// Create an empty strip on the output, the widths of columns are chosen from the `presets`.
+    /// Without presets, one third, half and two thirds of the output are used.
+    pub fn new(output: SizeAndPos<T>, presets: Vec<f32>) -> Self {
+        let presets = if presets.is_empty() {
+            vec![1. / 3., 0.5, 2. / 3.]
+        } else {
+            presets
+        };
+        let default_preset = presets.len() / 2;
+        Self {
+            output,
+            presets,
+            default_preset,
+            columns: Vec::new(),
+            focused: None,
+            offset: T::zero(),
+        }
+    }
+
+    /// All the columns from left to right
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// The focused window
+    pub fn focused(&self) -> Option<Id> {
+        self.focused
+    }
+
+    /// How far the view is scrolled from the left of the first column
+    pub fn offset(&self) -> T {
+        self.offset
+    }
+
+    /// Where the window is, the index of column and the index in the column
+    fn locate(&self, target: Id) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(index, column)| {
+            column
+                .windows
+                .iter()
+                .position(|id| *id == target)
+                .map(|row| (index, row))
+        })
+    }
+
+    fn column_width_in(&self, column: &Column, output_width: T) -> T {
+        let preset = self.presets[column.preset.min(self.presets.len() - 1)];
+        output_width.mul_f32(preset)
+    }
+
+    /// The offset which keeps the focused column of `columns` in the view, it is moved as little
+    /// as possible
+    fn scrolled(&self, columns: &[Column], output_width: T) -> T {
+        let Some(index) = self.focused.and_then(|id| {
+            columns
+                .iter()
+                .position(|column| column.windows.contains(&id))
+        }) else {
+            return T::zero();
+        };
+        let start = columns[..index]
+            .iter()
+            .map(|column| self.column_width_in(column, output_width))
+            .sum();
+        let width = self.column_width_in(&columns[index], output_width);
+        if start < self.offset {
+            start
+        } else if start + width > self.offset + output_width {
+            start + width - output_width
+        } else {
+            self.offset
+        }
+    }
+
+    /// Where every window is, relative to the scroll offset
+    pub fn placements(&self) -> Vec<Placement<T>> {
+        self.place(&self.columns, self.output, self.offset)
+    }
+
+    fn place(&self, columns: &[Column], output: SizeAndPos<T>, offset: T) -> Vec<Placement<T>> {
+        let SizeAndPos { size, position } = output;
+        let mut placements = Vec::new();
+        let mut start = T::zero();
+        for column in columns {
+            let width = self.column_width_in(column, size.width);
+            let visible = start < offset + size.width && start + width > offset;
+            let height = size.height / T::from_f32(column.windows.len() as f32);
+            let mut y = position.y;
+            for id in &column.windows {
+                placements.push(Placement {
+                    id: *id,
+                    size_pos: SizeAndPos {
+                        size: Size { width, height },
+                        position: Position {
+                            x: position.x + start - offset,
+                            y,
+                        },
+                    },
+                    visible,
+                });
+                y += height;
+            }
+            start += width;
+        }
+        placements
+    }
+
+    /// Check if the window can be seen on the output
+    pub fn is_visible(&self, target: Id) -> bool {
+        self.placements()
+            .iter()
+            .any(|placement| placement.id == target && placement.visible)
+    }
+
+    fn geometry(&self) -> Vec<(Id, SizeAndPos<T>)> {
+        self.placements()
+            .into_iter()
+            .map(|placement| (placement.id, placement.size_pos))
+            .collect()
+    }
+
+    /// Do the change, scroll to the focused column, then dispatch the windows which are moved
+    fn update<U, F>(&mut self, update: U, f: &mut F) -> Result<()>
+    where
+        U: FnOnce(&mut Self) -> Result<()>,
+        F: DispatchCallback<T>,
+    {
+        let before = self.geometry();
+        update(self)?;
+        self.scroll_to_focused();
+        dispatch_changed(&before, &self.geometry(), f);
+        Ok(())
+    }
+
+    fn scroll_to_focused(&mut self) {
+        self.offset = self.scrolled(&self.columns, self.output.size.width);
+    }
+
+    /// Put the window in a new column on the right of the focused one, and focus it
+    pub fn insert_column<F>(&mut self, id: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update(
+            |this| {
+                let index = this
+                    .focused
+                    .and_then(|focused| this.locate(focused))
+                    .map_or(this.columns.len(), |(index, _)| index + 1);
+                this.add_column(index, id);
+                Ok(())
+            },
+            f,
+        )
+    }
+
+    fn add_column(&mut self, index: usize, id: Id) {
+        self.columns.insert(
+            index,
+            Column {
+                windows: vec![id],
+                preset: self.default_preset,
+            },
+        );
+        self.focused = Some(id);
+    }
+
+    /// Put the window under the target in its column, and focus it
+    /// It only fails when the target id is not found
+    pub fn insert_in_column<F>(&mut self, id: Id, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update(
+            |this| {
+                let (index, row) = this.locate(target).ok_or(Error::ElementNotFound)?;
+                this.columns[index].windows.insert(row + 1, id);
+                this.focused = Some(id);
+                Ok(())
+            },
+            f,
+        )
+    }
+
+    /// Delete a window, the column is removed when it is empty.
+    /// When the focused one is deleted, the focus goes to its neighbor.
+    pub fn delete<F>(&mut self, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update(|this| this.remove(target), f)
+    }
+
+    fn remove(&mut self, target: Id) -> Result<()> {
+        let (index, row) = self.locate(target).ok_or(Error::ElementNotFound)?;
+        let column = &mut self.columns[index];
+        column.windows.remove(row);
+        let neighbor = match column.windows.get(row.saturating_sub(1)) {
+            Some(id) => Some(*id),
+            None => {
+                self.columns.remove(index);
+                let index = index.min(self.columns.len().saturating_sub(1));
+                self.columns.get(index).map(|column| column.windows[0])
+            }
+        };
+        if self.focused == Some(target) {
+            self.focused = neighbor;
+        }
+        Ok(())
+    }
+
+    /// Focus the window, the view is scrolled to it
+    /// It only fails when the target id is not found
+    pub fn focus<F>(&mut self, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update(
+            |this| {
+                this.locate(target).ok_or(Error::ElementNotFound)?;
+                this.focused = Some(target);
+                Ok(())
+            },
+            f,
+        )
+    }
+
+    /// Move the focus to the neighbor on the direction. The left and right neighbors are the
+    /// first windows of the columns beside.
+    /// It fails when nothing is focused, or there is no neighbor
+    pub fn focus_direction<F>(&mut self, direction: Direction, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let (index, row) = self
+            .focused
+            .and_then(|focused| self.locate(focused))
+            .ok_or(Error::ElementNotFound)?;
+        let neighbor = match direction {
+            Direction::Left => index
+                .checked_sub(1)
+                .and_then(|index| self.columns.get(index))
+                .map(|column| column.windows[0]),
+            Direction::Right => self.columns.get(index + 1).map(|column| column.windows[0]),
+            Direction::Top => row
+                .checked_sub(1)
+                .map(|row| self.columns[index].windows[row]),
+            Direction::Bottom => self.columns[index].windows.get(row + 1).copied(),
+        };
+        let neighbor = neighbor.ok_or(Error::ElementNotFound)?;
+        self.focus(neighbor, f)
+    }
+
+    /// Switch the column of the window to the next preset width
+    /// It only fails when the target id is not found
+    pub fn cycle_width<F>(&mut self, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.update(
+            |this| {
+                let (index, _) = this.locate(target).ok_or(Error::ElementNotFound)?;
+                let presets = this.presets.len();
+                let column = &mut this.columns[index];
+                column.preset = (column.preset + 1) % presets;
+                Ok(())
+            },
+            f,
+        )
+    }
+
+    /// Remap, when the output changed, invoke this function
+    pub fn remap<F>(&mut self, output: SizeAndPos<T>, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let _ = self.update(
+            |this| {
+                this.output = output;
+                Ok(())
+            },
+            f,
+        );
+    }
+}
+
+impl<T: MinusAbleMatUnit> Scrolling<T> {
+    /// The columns holding the windows of the map in their order. The windows which are not in
+    /// a column yet get their own columns at the end, and the order of the map is kept, so a
+    /// swap in the map swaps the windows in the columns.
+    fn synced(&self, windows: &[Id]) -> Vec<Column> {
+        let mut columns: Vec<Column> = self
+            .columns
+            .iter()
+            .map(|column| Column {
+                windows: column
+                    .windows
+                    .iter()
+                    .filter(|id| windows.contains(id))
+                    .copied()
+                    .collect(),
+                preset: column.preset,
+            })
+            .filter(|column| !column.windows.is_empty())
+            .collect();
+        for id in windows {
+            if !columns.iter().any(|column| column.windows.contains(id)) {
+                columns.push(Column {
+                    windows: vec![*id],
+                    preset: self.default_preset,
+                });
+            }
+        }
+        let mut order = windows.iter();
+        for column in &mut columns {
+            for id in &mut column.windows {
+                if let Some(o_id) = order.next() {
+                    *id = *o_id;
+                }
+            }
+        }
+        columns
+    }
+
+    /// Change the columns by `update`, then give the order of their windows back to the map
+    fn sync<U>(&mut self, windows: &mut Vec<Id>, update: U) -> Result<()>
+    where
+        U: FnOnce(&mut Self) -> Result<()>,
+    {
+        self.columns = self.synced(windows);
+        update(self)?;
+        *windows = self
+            .columns
+            .iter()
+            .flat_map(|column| column.windows.iter().copied())
+            .collect();
+        Ok(())
+    }
+}
+
+impl<T: MinusAbleMatUnit + Debug + 'static> LayoutPolicy<T> for Scrolling<T> {
+    fn arrange(&self, windows: &[Id], size_pos: SizeAndPos<T>) -> Vec<Placement<T>> {
+        let columns = self.synced(windows);
+        let offset = self.scrolled(&columns, size_pos.size.width);
+        self.place(&columns, size_pos, offset)
+    }
+
+    fn on_insert(
+        &mut self,
+        windows: &mut Vec<Id>,
+        id: Id,
+        target: Option<Id>,
+        end: bool,
+    ) -> Result<()> {
+        self.sync(windows, |this| {
+            let index = match target {
+                Some(target) if !this.columns.is_empty() => {
+                    let (index, _) = this.locate(target).ok_or(Error::ElementNotFound)?;
+                    if end { index + 1 } else { index }
+                }
+                _ => this
+                    .focused
+                    .and_then(|focused| this.locate(focused))
+                    .map_or(this.columns.len(), |(index, _)| index + 1),
+            };
+            this.add_column(index, id);
+            Ok(())
+        })?;
+        self.offset = self.scrolled(&self.columns, self.output.size.width);
+        Ok(())
+    }
+
+    fn on_delete(&mut self, windows: &mut Vec<Id>, target: Id) -> Result<()> {
+        self.sync(windows, |this| this.remove(target))?;
+        self.offset = self.scrolled(&self.columns, self.output.size.width);
+        Ok(())
+    }
+}
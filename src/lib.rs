@@ -1,19 +1,41 @@
+mod balance;
+mod dsl;
+mod event;
+mod history;
+#[cfg(feature = "i3")]
+pub mod i3;
+pub mod layout;
+mod monocle;
+mod normalize;
+mod placeholder;
+mod preview;
+mod render;
+mod snapshot;
+mod svg;
 #[cfg(test)]
 mod tests;
+mod transaction;
+mod transform;
 mod utils;
 
-use std::collections::HashMap;
 use std::hash::Hash;
 use std::sync::atomic::{self, AtomicU64};
 pub mod error;
 
 pub use error::FlyjaError as Error;
+pub use event::{Dispatch, LayoutEvent, LayoutObserver};
+pub use placeholder::{Criteria, Placeholder, WindowProps};
+pub use render::RenderStyle;
+pub use snapshot::FORMAT_VERSION;
 
-pub use crate::utils::{Direction, InsertWay, Percentage, Position, Size, SizeAndPos};
+pub use crate::utils::{Direction, InsertWay, Percentage, Position, Rotation, Size, SizeAndPos};
 
+use crate::history::History;
+use crate::layout::{AutoState, Autotiling, InsertPolicy};
 use crate::utils::{MapUnit, MinusAbleMatUnit};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// The id of the window.
 ///
 /// Internally Iced reserves `window::Id::MAIN` for the first window spawned.
@@ -38,6 +60,11 @@ impl Id {
         Id(COUNT.fetch_add(1, atomic::Ordering::Relaxed))
     }
 
+    /// Make [`Id::unique`] only give the ids after this one, as it is used by a loaded tree
+    pub(crate) fn reserve(self) {
+        COUNT.fetch_max(self.0 + 1, atomic::Ordering::Relaxed);
+    }
+
     /// It is used in unit test
     #[cfg(test)]
     fn next(&self) -> Id {
@@ -46,45 +73,111 @@ impl Id {
 }
 
 #[derive(Debug, Clone)]
-pub struct TopElementMap<T: MapUnit = f32>(Element<T>);
+pub struct TopElementMap<T: MapUnit = f32> {
+    root: Element<T>,
+    /// Normalize the tree after every operation which changes its shape
+    auto_normalize: bool,
+    /// The automatic layout in use, the tree is rebuilt by it
+    auto: Option<AutoState<T>>,
+    /// How a pushed window is placed when the tree is edited by hand
+    insert_policy: InsertPolicy,
+    /// How a pushed window is split when the tree is edited by hand
+    autotiling: Option<Autotiling<T>>,
+    /// The window inserted most recently
+    recent: Option<Id>,
+    /// The focused window
+    focused: Option<Id>,
+    /// Every window takes the whole map, only the focused one is visible
+    monocle: bool,
+    /// The slots waiting for windows to swallow them
+    placeholders: Vec<Placeholder>,
+    /// The steps to undo and redo, [`None`] means they are not recorded
+    history: Option<History<T>>,
+}
 impl<T: MinusAbleMatUnit> TopElementMap<T> {
     /// create a new [`TopElementMap<T>`]
     pub fn new(size_pos: SizeAndPos<T>) -> Self {
-        Self(Element::new(size_pos))
+        Self {
+            root: Element::new(size_pos),
+            auto_normalize: false,
+            auto: None,
+            insert_policy: InsertPolicy::default(),
+            autotiling: None,
+            recent: None,
+            focused: None,
+            monocle: false,
+            placeholders: Vec::new(),
+            history: None,
+        }
+    }
+
+    /// The top element of the map
+    pub fn root(&self) -> &Element<T> {
+        &self.root
+    }
+
+    /// Normalize the tree after every `insert`, `delete`, `drag_and_drop` and split change
+    pub fn set_auto_normalize(&mut self, auto_normalize: bool) {
+        self.auto_normalize = auto_normalize;
+        self.after_change();
+    }
+
+    /// Flatten the containers nested in a container with the same way, and collapse the
+    /// containers with only one element. The windows won't change on screen.
+    /// It only fails when the windows are placed by the automatic layout
+    pub fn normalize(&mut self) -> Result<()> {
+        self.check_tree()?;
+        self.root.normalize();
+        Ok(())
+    }
+
+    /// The tree of an automatic layout which only places the windows is not where they are, so
+    /// it cannot be edited
+    fn check_tree(&self) -> Result<()> {
+        if self.auto.as_ref().is_some_and(|auto| auto.is_placed()) {
+            return Err(Error::TreeNotEditable);
+        }
+        Ok(())
+    }
+
+    fn after_change(&mut self) {
+        if self.auto_normalize {
+            self.root.normalize();
+        }
     }
 
     /// Get the information of size and position
     pub fn size_pos(&self) -> SizeAndPos<T> {
-        self.0.size_pos()
+        self.root.size_pos()
     }
 
     pub fn position(&self) -> Position<T> {
-        self.0.position()
+        self.root.position()
     }
 
     /// return the size of current container
     pub fn size(&self) -> Size<T> {
-        self.0.size()
+        self.root.size()
     }
 
     /// returnt the width of current container
     pub fn width(&self) -> T {
-        self.0.width()
+        self.root.width()
     }
 
     /// return the size of the container
     pub fn height(&self) -> T {
-        self.0.height()
+        self.root.height()
     }
 
     /// check if the container contains a window
     pub fn has_id(&self, target: Id) -> bool {
-        self.0.has_id(target)
+        self.root.has_id(target)
     }
 
     /// Find a window with id, and get all information
     pub fn find_window(&self, target: Id) -> Option<&Element<T>> {
-        self.0.find_window(target)
+        self.root.find_window(target)
     }
 
     /// Swap two elements
@@ -92,7 +185,19 @@ impl<T: MinusAbleMatUnit> TopElementMap<T> {
     where
         F: DispatchCallback<T>,
     {
-        self.0.swap(id, target, f)
+        self.record(|map| {
+            let f = &mut map.view(f);
+            // NOTE: the automatic layout keeps the tree until the windows change, so only the order
+            // need to be updated
+            if let Some(auto) = &mut map.auto {
+                auto.swap(id, target)?;
+                if auto.is_placed() {
+                    map.relayout(f);
+                    return Ok(());
+                }
+            }
+            map.root.swap(id, target, f)
+        })
     }
 
     /// Remap, when the container or the display changed, invoke this function
@@ -100,7 +205,42 @@ impl<T: MinusAbleMatUnit> TopElementMap<T> {
     where
         F: DispatchCallback<T>,
     {
-        self.0.remap(c_size_pos, f);
+        let _ = self.record(|map| {
+            if map.monocle {
+                // NOTE: every window takes the whole new map, the view out of the monocle mode
+                // only hides the placeholders
+                map.monocle = false;
+                let f = &mut map.view(f);
+                map.remap(c_size_pos, &mut ());
+                map.monocle = true;
+                for (id, _) in map.root.windows() {
+                    f.callback(id, c_size_pos);
+                }
+                return Ok(());
+            }
+            let f = &mut map.view(f);
+            if let Some(auto) = &map.auto
+                && auto.is_placed()
+            {
+                map.root.set_size_and_pos(c_size_pos);
+                map.relayout(f);
+                return Ok(());
+            }
+            if map.auto.is_none()
+                && let InsertPolicy::Dwindle(dwindle) = map.insert_policy
+                && !dwindle.preserve_split
+                && !dwindle.spiral
+            {
+                map.root.remap(c_size_pos, &mut ());
+                map.root.reorient();
+                for (id, size_pos) in map.root.windows() {
+                    f.callback(id, size_pos);
+                }
+                return Ok(());
+            }
+            map.root.remap(c_size_pos, f);
+            Ok(())
+        });
     }
 
     /// Delete a window from the map or container. If failed, return a error
@@ -109,7 +249,21 @@ impl<T: MinusAbleMatUnit> TopElementMap<T> {
     where
         F: DispatchCallback<T>,
     {
-        self.0.delete(target, f)
+        self.record(|map| {
+            let f = &mut map.view(f);
+            let before = map.root.windows();
+            if let Some(auto) = &mut map.auto {
+                auto.delete(target)?;
+                map.relayout(f);
+            } else {
+                map.root.delete(target, f)?;
+                map.after_change();
+            }
+            map.placeholders
+                .retain(|placeholder| placeholder.slot != target);
+            map.fix_focus(&before);
+            Ok(())
+        })
     }
 
     /// The return shows the new inserted position. it should be saved. but you can know it during
@@ -119,7 +273,19 @@ impl<T: MinusAbleMatUnit> TopElementMap<T> {
     where
         F: DispatchCallback<T>,
     {
-        self.0.insert(id, target, direction, f)
+        self.record(|map| {
+            let f = &mut map.view(f);
+            if let Some(auto) = &mut map.auto {
+                auto.insert(id, Some(target), direction.is_end())?;
+                map.recent = Some(id);
+                map.relayout(f);
+                return Ok(());
+            }
+            map.root.insert(id, target, direction, f)?;
+            map.recent = Some(id);
+            map.after_change();
+            Ok(())
+        })
     }
 
     /// The return shows the new inserted position. it should be saved. but you can know it during
@@ -129,7 +295,201 @@ impl<T: MinusAbleMatUnit> TopElementMap<T> {
     where
         F: DispatchCallback<T>,
     {
-        self.0.insert_new(id, target, way, f)
+        self.insert(id, target, way.into(), f)
+    }
+
+    /// Insert a window without picking the target. In an automatic layout it is put at the end,
+    /// else the window is placed by the [`InsertPolicy`], and split by the [`Autotiling`] when
+    /// it is turned on.
+    pub fn push<F>(&mut self, id: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.record(|map| map.push_window(id, f))
+    }
+
+    /// The [`TopElementMap::push`] which is not recorded by itself, for the operations made of it
+    pub(crate) fn push_window<F>(&mut self, id: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        if let Some(auto) = &mut self.auto {
+            auto.insert(id, None, true)?;
+            self.relayout(f);
+            return Ok(());
+        }
+        // NOTE: the target is not used when the map is empty
+        let last = self.root.windows().last().map_or(id, |(last, _)| *last);
+        match self.insert_policy {
+            InsertPolicy::Last if self.autotiling.is_some() && self.root.window_count() > 0 => {
+                self.insert_auto(id, last, f)
+            }
+            InsertPolicy::Last => self.insert_new(id, last, InsertWay::default(), f),
+            InsertPolicy::Dwindle(_) if self.root.window_count() == 0 => {
+                self.insert_new(id, last, InsertWay::default(), f)
+            }
+            InsertPolicy::Dwindle(dwindle) => {
+                let target = self
+                    .recent
+                    .filter(|recent| self.root.find_window(*recent).is_some())
+                    .unwrap_or(last);
+                self.dwindle_insert(id, target, dwindle, f)
+            }
+        }
+    }
+
+    /// Balance the whole map, so every window gets about the same area
+    /// It only fails when the windows are placed by the automatic layout
+    pub fn balance<F>(&mut self, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.check_tree()?;
+        let f = &mut self.view(f);
+        self.root.balance(f);
+        Ok(())
+    }
+
+    /// Balance the container which holds the target window
+    /// It fails when the target id is not found, or the windows are placed by the automatic
+    /// layout
+    pub fn balance_container<F>(&mut self, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.check_tree()?;
+        let f = &mut self.view(f);
+        let Some(container) = self.root.find_container_mut(target) else {
+            // NOTE: a window without container is already balanced
+            return self
+                .root
+                .find_window(target)
+                .map(|_| ())
+                .ok_or(Error::ElementNotFound);
+        };
+        container.balance(f);
+        Ok(())
+    }
+
+    /// Set the way of the container which holds the target window
+    /// It fails when the target id is not found, the window is not in a container, or the windows
+    /// are placed by the automatic layout
+    pub fn set_split<F>(&mut self, target: Id, way: InsertWay, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        self.target_container(target)?.set_way(way, f);
+        self.after_change();
+        Ok(())
+    }
+
+    /// Toggle the way of the container which holds the target window, like `layout toggle split`
+    /// in i3
+    /// It fails when the target id is not found, the window is not in a container, or the windows
+    /// are placed by the automatic layout
+    pub fn toggle_split<F>(&mut self, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        self.target_container(target)?.toggle_way(f);
+        self.after_change();
+        Ok(())
+    }
+
+    /// Rotate the whole map clockwise
+    /// It only fails when the windows are placed by the automatic layout
+    pub fn rotate<F>(&mut self, rotation: Rotation, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.check_tree()?;
+        let f = &mut self.view(f);
+        self.root.rotate(rotation, f);
+        Ok(())
+    }
+
+    /// Rotate the container which holds the target window clockwise
+    /// It fails when the target id is not found, the window is not in a container, or the windows
+    /// are placed by the automatic layout
+    pub fn rotate_container<F>(&mut self, target: Id, rotation: Rotation, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        self.target_container(target)?.rotate(rotation, f);
+        self.after_change();
+        Ok(())
+    }
+
+    /// Flip the whole map on the `way` axis
+    /// It only fails when the windows are placed by the automatic layout
+    pub fn mirror<F>(&mut self, way: InsertWay, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.check_tree()?;
+        let f = &mut self.view(f);
+        self.root.mirror(way, f);
+        Ok(())
+    }
+
+    /// Flip the container which holds the target window on the `way` axis
+    /// It fails when the target id is not found, the window is not in a container, or the windows
+    /// are placed by the automatic layout
+    pub fn mirror_container<F>(&mut self, target: Id, way: InsertWay, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        self.target_container(target)?.mirror(way, f);
+        self.after_change();
+        Ok(())
+    }
+
+    /// Swap the vertical and horizontal axis of the whole map
+    /// It only fails when the windows are placed by the automatic layout
+    pub fn transpose<F>(&mut self, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        self.check_tree()?;
+        let f = &mut self.view(f);
+        self.root.transpose(f);
+        Ok(())
+    }
+
+    /// Swap the vertical and horizontal axis of the container which holds the target window
+    /// It fails when the target id is not found, the window is not in a container, or the windows
+    /// are placed by the automatic layout
+    pub fn transpose_container<F>(&mut self, target: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let f = &mut self.view(f);
+        self.target_container(target)?.transpose(f);
+        self.after_change();
+        Ok(())
+    }
+
+    fn target_container(&mut self, target: Id) -> Result<&mut Element<T>> {
+        self.check_tree()?;
+        if self.root.find_window(target).is_none() {
+            return Err(Error::ElementNotFound);
+        }
+        self.root
+            .find_container_mut(target)
+            .ok_or(Error::ContainerNotFound)
+    }
+
+    /// Wrap a window in a new container, the next windows inserted into it follow the `way`
+    /// It fails when the target id is not found, or the windows are placed by the automatic
+    /// layout
+    pub fn wrap(&mut self, target: Id, way: InsertWay) -> Result<()> {
+        self.check_tree()?;
+        self.root.wrap(target, way)
     }
 
     // TODO: unit tests
@@ -143,7 +503,23 @@ impl<T: MinusAbleMatUnit> TopElementMap<T> {
     where
         F: DispatchCallback<T>,
     {
-        self.0.drag_resize(transfer, direction, target, f)
+        self.record(|map| {
+            let f = &mut map.view(f);
+            if let Some(auto) = &mut map.auto {
+                let size_pos = map.root.size_pos();
+                if auto
+                    .policy
+                    .on_resize(&auto.windows, target, transfer, direction, size_pos)?
+                {
+                    map.relayout(f);
+                    return Ok(());
+                }
+                if auto.is_placed() {
+                    return Err(Error::DragIllegal);
+                }
+            }
+            map.root.drag_resize(transfer, direction, target, f)
+        })
     }
 
     /// drag and drop an element
@@ -157,19 +533,26 @@ impl<T: MinusAbleMatUnit> TopElementMap<T> {
     where
         F: DispatchCallback<T>,
     {
-        // NOTE: this will make the size change will only happened once
-        let mut ids = HashMap::new();
-        self.0
-            .drag_and_drop(id, target, direction, &mut |id, new_size_and_pos| {
-                ids.insert(id, new_size_and_pos);
-            })?;
-        for (id, size_pos) in ids.iter() {
-            f.callback(*id, *size_pos);
-        }
-        Ok(())
+        // NOTE: the window is taken out before it is put back, so it is done in a transaction. It
+        // is not lost when it cannot be put back, and every window is dispatched once
+        self.transaction(f, |map| {
+            if let Some(auto) = &mut map.auto {
+                if !auto.contains(target) {
+                    return Err(Error::ElementNotFound);
+                }
+                let _ = auto.delete(id);
+                auto.insert(id, Some(target), direction.is_end())?;
+                map.relayout(&mut ());
+                return Ok(());
+            }
+            map.root.drag_and_drop(id, target, direction, &mut ())?;
+            map.after_change();
+            Ok(())
+        })
     }
 }
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Element<T: MapUnit = f32> {
     /// Only when the container is empty
     /// It contains the size and position information of the container
@@ -190,6 +573,9 @@ pub enum Element<T: MapUnit = f32> {
         size_pos: SizeAndPos<T>,
         // This storage current percentage in the container (if it is in a container)
         percent: Percentage,
+        /// The container is made on purpose by [`Element::wrap`], new windows inserted into it
+        /// follow its way, and it is kept even only one element is left
+        pinned: bool,
     },
     Horizontal {
         /// All the [`Element<T>`] in the container
@@ -198,9 +584,28 @@ pub enum Element<T: MapUnit = f32> {
         size_pos: SizeAndPos<T>,
         // This storage current percentage in the container (if it is in a container)
         percent: Percentage,
+        /// The container is made on purpose by [`Element::wrap`], new windows inserted into it
+        /// follow its way, and it is kept even only one element is left
+        pinned: bool,
     },
 }
 
+/// Dispatch the windows in `after` whose size and position are not the same as in `before`
+fn dispatch_changed<T, F>(before: &[(Id, SizeAndPos<T>)], after: &[(Id, SizeAndPos<T>)], f: &mut F)
+where
+    T: MapUnit,
+    F: DispatchCallback<T>,
+{
+    for (id, size_pos) in after {
+        let unchanged = before
+            .iter()
+            .any(|(o_id, o_size_pos)| o_id == id && o_size_pos == size_pos);
+        if !unchanged {
+            f.callback(*id, *size_pos);
+        }
+    }
+}
+
 pub trait DispatchCallback<T: MapUnit> {
     fn callback(&mut self, id: Id, size_pos: SizeAndPos<T>);
 }
@@ -294,6 +699,7 @@ impl<T: MinusAbleMatUnit> Element<T> {
                 elements,
                 size_pos,
                 percent,
+                ..
             } => {
                 *size_pos += change;
                 *percent += diff_percent;
@@ -326,6 +732,7 @@ impl<T: MinusAbleMatUnit> Element<T> {
                 elements,
                 size_pos,
                 percent,
+                ..
             } => {
                 *size_pos += change;
                 *percent += diff_percent;
@@ -380,6 +787,53 @@ impl<T: MinusAbleMatUnit> Element<T> {
         matches!(self, Self::Window { .. })
     }
 
+    /// Check if the element is a container made on purpose by [`Element::wrap`]
+    pub fn is_pinned(&self) -> bool {
+        match self {
+            Self::Vertical { pinned, .. } | Self::Horizontal { pinned, .. } => *pinned,
+            _ => false,
+        }
+    }
+
+    /// The way the children are placed, only containers have it
+    pub fn way(&self) -> Option<InsertWay> {
+        match self {
+            Self::Vertical { .. } => Some(InsertWay::Vertical),
+            Self::Horizontal { .. } => Some(InsertWay::Horizontal),
+            _ => None,
+        }
+    }
+
+    /// How many windows are in the element
+    pub fn window_count(&self) -> usize {
+        match self {
+            Self::EmptyOutput(_) => 0,
+            Self::Window { .. } => 1,
+            Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } => {
+                elements.iter().map(Self::window_count).sum()
+            }
+        }
+    }
+
+    /// All the windows in the element with their size and position, in the order of the tree
+    pub fn windows(&self) -> Vec<(Id, SizeAndPos<T>)> {
+        let mut windows = Vec::new();
+        self.collect_windows(&mut windows);
+        windows
+    }
+
+    fn collect_windows(&self, windows: &mut Vec<(Id, SizeAndPos<T>)>) {
+        match self {
+            Self::EmptyOutput(_) => {}
+            Self::Window { id, size_pos, .. } => windows.push((*id, *size_pos)),
+            Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } => {
+                for element in elements {
+                    element.collect_windows(windows);
+                }
+            }
+        }
+    }
+
     /// Check if current container contains a window
     pub fn has_id(&self, target: Id) -> bool {
         match self {
@@ -428,6 +882,38 @@ impl<T: MinusAbleMatUnit> Element<T> {
         }
     }
 
+    /// Find the container which holds the window directly
+    fn find_container_mut(&mut self, target: Id) -> Option<&mut Self> {
+        let holds = match self {
+            Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } => elements
+                .iter()
+                .any(|element| matches!(element, Self::Window { id, .. } if *id == target)),
+            _ => return None,
+        };
+        if holds {
+            return Some(self);
+        }
+        match self {
+            Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } => elements
+                .iter_mut()
+                .find_map(|element| element.find_container_mut(target)),
+            _ => None,
+        }
+    }
+
+    /// Remap the element in place, and only dispatch the windows which are changed compared with
+    /// `before`
+    fn remap_changed<F>(&mut self, before: &[(Id, SizeAndPos<T>)], f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let mut after = Vec::new();
+        self.remap(self.size_pos(), &mut |id, size_pos| {
+            after.push((id, size_pos))
+        });
+        dispatch_changed(before, &after, f);
+    }
+
     // NOTE: not just find it, but return the insert position
     fn find_duo_windows_mut(
         &mut self,
@@ -886,11 +1372,13 @@ impl<T: MinusAbleMatUnit> Element<T> {
                 elements,
                 percent,
                 size_pos,
+                pinned,
             }
             | Self::Horizontal {
                 elements,
                 percent,
                 size_pos,
+                pinned,
             } => {
                 let mut position: Option<usize> = None;
                 let mut window_s_a_p: Option<SizeAndPos<T>> = None;
@@ -899,16 +1387,15 @@ impl<T: MinusAbleMatUnit> Element<T> {
                 // else it shrink
                 let mut target_percent: Option<Size> = None;
                 for (index, element) in elements.iter_mut().enumerate() {
-                    if let Self::Window {
-                        id,
-                        size_pos,
-                        percent,
-                    } = element
-                        && *id == target
-                    {
+                    // NOTE: a pinned container which only holds the target disappears with it
+                    let lone_target = match element {
+                        Self::Window { id, .. } => *id == target,
+                        _ => element.window_count() == 1 && element.find_window(target).is_some(),
+                    };
+                    if lone_target {
                         position = Some(index);
-                        window_s_a_p = Some(*size_pos);
-                        target_percent = Some(*percent);
+                        window_s_a_p = Some(element.size_pos());
+                        target_percent = Some(element.percent());
                         break;
                     }
                     if element.delete(target, f).is_ok() {
@@ -922,6 +1409,12 @@ impl<T: MinusAbleMatUnit> Element<T> {
                 };
                 elements.remove(pos);
 
+                // NOTE: only a pinned container can lose its last window
+                if elements.is_empty() {
+                    *self = Self::EmptyOutput(*size_pos);
+                    return Ok(());
+                }
+
                 let start = pos == 0;
                 let adjust_pos = if start { 0 } else { pos - 1 };
                 let expand_way = Direction::expend_way(fit_way, start);
@@ -941,7 +1434,7 @@ impl<T: MinusAbleMatUnit> Element<T> {
                 // Then we remove the deleted guy
 
                 // it the element only one existed, downgrade it
-                if elements.len() == 1 {
+                if elements.len() == 1 && !*pinned {
                     let o_percent = *percent;
                     let o_size_pos = *size_pos;
                     // first, we clone all the information in the element[0]
@@ -958,6 +1451,49 @@ impl<T: MinusAbleMatUnit> Element<T> {
         }
     }
 
+    /// Wrap a window in a pinned container with only itself inside, then the next windows
+    /// inserted into it follow the `way`, like the split mode of i3.
+    /// If the window is already the only element of a container, that container is used.
+    pub fn wrap(&mut self, target: Id, way: InsertWay) -> Result<()> {
+        if let Some(container) = self.find_container_mut(target)
+            && let Self::Vertical {
+                elements, pinned, ..
+            }
+            | Self::Horizontal {
+                elements, pinned, ..
+            } = container
+            && elements.len() == 1
+        {
+            *pinned = true;
+            // NOTE: the only window always takes the whole container, so nothing is changed
+            container.set_way(way, &mut ());
+            return Ok(());
+        }
+        let Some(window) = self.find_window_mut(target) else {
+            return Err(Error::ElementNotFound);
+        };
+        let size_pos = window.size_pos();
+        let percent = window.percent();
+        let mut inner = window.clone();
+        inner.set_percentage(Size::whole());
+        let elements = vec![inner];
+        *window = match way {
+            InsertWay::Vertical => Self::Vertical {
+                elements,
+                size_pos,
+                percent,
+                pinned: true,
+            },
+            InsertWay::Horizontal => Self::Horizontal {
+                elements,
+                size_pos,
+                percent,
+                pinned: true,
+            },
+        };
+        Ok(())
+    }
+
     /// Drag a window from map or other place and drop it
     pub fn drag_and_drop<F>(
         &mut self,
@@ -1038,16 +1574,23 @@ impl<T: MinusAbleMatUnit> Element<T> {
                         elements,
                         size_pos: origin_size_pos,
                         percent: old_percent,
+                        pinned: false,
                     },
                     Direction::Left | Direction::Right => Element::Horizontal {
                         elements,
                         size_pos: origin_size_pos,
                         percent: old_percent,
+                        pinned: false,
                     },
                 };
                 Ok(())
             }
-            Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } => {
+            Self::Vertical {
+                elements, pinned, ..
+            }
+            | Self::Horizontal {
+                elements, pinned, ..
+            } => {
                 let mut to_insert_index: Option<usize> = None;
                 let mut to_return: Option<SizeAndPos<T>> = None;
                 let mut new_percent: Option<Size> = None;
@@ -1059,6 +1602,12 @@ impl<T: MinusAbleMatUnit> Element<T> {
                     } = element
                         && *o_id == target
                     {
+                        // NOTE: the pinned container decides the way, only keep the side
+                        let direction = if *pinned {
+                            Direction::expend_way(fit_way, !direction.is_end())
+                        } else {
+                            direction
+                        };
                         if fit_way.fit_direction(direction) {
                             let new_size_pos = size_pos.split(direction);
                             *percent = percent.split(2., direction);
@@ -0,0 +1,191 @@
+use crate::layout::Placement;
+use crate::utils::{MapUnit, MinusAbleMatUnit};
+use crate::{DispatchCallback, Error, Id, Result, SizeAndPos, TopElementMap, dispatch_changed};
+
+/// The callback seen through the monocle mode. Every window takes the whole map in it, so only
+/// the windows which are not placed yet are dispatched. The placeholders are never dispatched.
+pub(crate) struct View<'a, T: MapUnit, F> {
+    f: &'a mut F,
+    monocle: Option<(SizeAndPos<T>, Vec<Id>)>,
+    placeholders: Vec<Id>,
+}
+
+impl<T: MapUnit, F: DispatchCallback<T>> DispatchCallback<T> for View<'_, T, F> {
+    fn callback(&mut self, id: Id, size_pos: SizeAndPos<T>) {
+        if self.placeholders.contains(&id) {
+            return;
+        }
+        let Some((whole, placed)) = &mut self.monocle else {
+            self.f.callback(id, size_pos);
+            return;
+        };
+        if !placed.contains(&id) {
+            placed.push(id);
+            self.f.callback(id, *whole);
+        }
+    }
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    pub(crate) fn view<'a, F>(&self, f: &'a mut F) -> View<'a, T, F> {
+        let monocle = self.monocle.then(|| {
+            let placed = self.root.windows().into_iter().map(|(id, _)| id).collect();
+            (self.root.size_pos(), placed)
+        });
+        let placeholders = self
+            .placeholders
+            .iter()
+            .map(|placeholder| placeholder.slot)
+            .collect();
+        View {
+            f,
+            monocle,
+            placeholders,
+        }
+    }
+
+    /// Check if the map is in the monocle mode
+    pub fn is_monocle(&self) -> bool {
+        self.monocle
+    }
+
+    /// Turn on or off the monocle mode. In it every window takes the whole map, and only the
+    /// focused one is visible.
+    ///
+    /// The tree is kept as it is in the monocle mode, so it comes back with the same shape and
+    /// percent when it is turned off. Only the windows which are moved are dispatched.
+    pub fn set_monocle<F>(&mut self, monocle: bool, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        if self.monocle == monocle {
+            return;
+        }
+        // NOTE: the moves of the monocle mode are dispatched here, the view only hides the
+        // placeholders
+        let f = &mut View {
+            monocle: None,
+            ..self.view(f)
+        };
+        let before = self.geometry();
+        self.monocle = monocle;
+        if monocle && self.focused.is_none() {
+            self.focused = self.focusable().first().copied();
+        }
+        dispatch_changed(&before, &self.geometry(), f);
+    }
+
+    fn geometry(&self) -> Vec<(Id, SizeAndPos<T>)> {
+        self.placements()
+            .into_iter()
+            .map(|placement| (placement.id, placement.size_pos))
+            .collect()
+    }
+
+    /// Where every window is placed, in the order of the tree.
+    /// In the monocle mode they all take the whole map, and only the focused one is visible.
+    pub fn placements(&self) -> Vec<Placement<T>> {
+        if !self.monocle
+            && let Some(placements) = self.auto.as_ref().and_then(|auto| auto.placements.clone())
+        {
+            return placements;
+        }
+        let whole = self.root.size_pos();
+        self.root
+            .windows()
+            .into_iter()
+            .map(|(id, size_pos)| {
+                if self.monocle {
+                    Placement {
+                        id,
+                        size_pos: whole,
+                        visible: self.focused == Some(id),
+                    }
+                } else {
+                    Placement {
+                        id,
+                        size_pos,
+                        visible: true,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The focused window
+    pub fn focused(&self) -> Option<Id> {
+        self.focused
+    }
+
+    /// Focus a window
+    /// It fails when the target id is not found, or it is the slot of a placeholder
+    pub fn focus(&mut self, target: Id) -> Result<()> {
+        if !self.focusable().contains(&target) {
+            return Err(Error::ElementNotFound);
+        }
+        self.focused = Some(target);
+        Ok(())
+    }
+
+    /// Focus the next window in the order of the tree, it goes back to the first one after the
+    /// last one. The new focused window is returned.
+    pub fn focus_next(&mut self) -> Option<Id> {
+        self.cycle_focus(true)
+    }
+
+    /// Focus the previous window in the order of the tree, it goes to the last one before the
+    /// first one. The new focused window is returned.
+    pub fn focus_prev(&mut self) -> Option<Id> {
+        self.cycle_focus(false)
+    }
+
+    fn cycle_focus(&mut self, next: bool) -> Option<Id> {
+        let windows = self.focusable();
+        let len = windows.len();
+        let current = self
+            .focused
+            .and_then(|focused| windows.iter().position(|id| *id == focused));
+        let index = match (current, next) {
+            _ if len == 0 => return None,
+            (None, _) => 0,
+            (Some(index), true) => (index + 1) % len,
+            (Some(index), false) => (index + len - 1) % len,
+        };
+        self.focused = Some(windows[index]);
+        self.focused
+    }
+
+    /// The windows in the order of the tree, without the slots of the placeholders
+    fn focusable(&self) -> Vec<Id> {
+        self.root
+            .windows()
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|id| {
+                !self
+                    .placeholders
+                    .iter()
+                    .any(|placeholder| placeholder.slot == *id)
+            })
+            .collect()
+    }
+
+    /// Keep the focus on a window in the map, the neighbor in the order gets it when the focused
+    /// one is gone
+    pub(crate) fn fix_focus(&mut self, before: &[(Id, SizeAndPos<T>)]) {
+        let Some(focused) = self.focused else {
+            return;
+        };
+        if self.root.find_window(focused).is_some() {
+            return;
+        }
+        let index = before
+            .iter()
+            .position(|(id, _)| *id == focused)
+            .unwrap_or(0);
+        let windows = self.focusable();
+        self.focused = windows
+            .get(index.min(windows.len().saturating_sub(1)))
+            .copied();
+    }
+}
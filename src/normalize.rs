@@ -0,0 +1,64 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{Element, Percentage};
+
+impl<T: MinusAbleMatUnit> Element<T> {
+    /// Flatten the containers nested in a container with the same way, the percent of their
+    /// elements is multiplied through. The containers with only one element are replaced by the
+    /// element. Pinned containers are kept.
+    ///
+    /// The size and position of windows won't change, so nothing need to be dispatched.
+    pub fn normalize(&mut self) {
+        let Some(way) = self.way() else {
+            return;
+        };
+        let (Self::Vertical {
+            elements,
+            size_pos,
+            percent,
+            pinned,
+        }
+        | Self::Horizontal {
+            elements,
+            size_pos,
+            percent,
+            pinned,
+        }) = self
+        else {
+            unreachable!()
+        };
+        let mut flatten = Vec::with_capacity(elements.len());
+        for mut element in std::mem::take(elements) {
+            element.normalize();
+            if element.way() != Some(way) || element.is_pinned() {
+                flatten.push(element);
+                continue;
+            }
+            let share = element.percent().on_axis(way);
+            let (Self::Vertical {
+                elements: inner, ..
+            }
+            | Self::Horizontal {
+                elements: inner, ..
+            }) = element
+            else {
+                unreachable!()
+            };
+            for mut inner_element in inner {
+                let inner_share = inner_element.percent().on_axis(way);
+                inner_element.set_percentage(Percentage::along(way, share * inner_share));
+                flatten.push(inner_element);
+            }
+        }
+        *elements = flatten;
+
+        if elements.len() == 1 && !*pinned {
+            let (o_percent, o_size_pos) = (*percent, *size_pos);
+            let Some(element) = elements.pop() else {
+                unreachable!()
+            };
+            *self = element;
+            self.set_percentage(o_percent);
+            self.set_size_and_pos(o_size_pos);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::{Duration, Instant};
+
+use crate::utils::MinusAbleMatUnit;
+use crate::{DispatchCallback, Element, Error, Id, Result, TopElementMap};
+
+/// What a window is known by before it gets its id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WindowProps<'a> {
+    /// The app id of a wayland window
+    pub app_id: Option<&'a str>,
+    pub title: Option<&'a str>,
+    /// The class of a X11 window
+    pub class: Option<&'a str>,
+}
+
+/// Which windows can swallow a placeholder. Every criterion which is given must match, the
+/// `title` is a pattern where `*` matches any text and `?` matches one character.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Criteria {
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    pub class: Option<String>,
+}
+
+impl Criteria {
+    /// Check if the window matches the criteria
+    pub fn matches(&self, window: &WindowProps) -> bool {
+        let exact = |criterion: &Option<String>, value: Option<&str>| {
+            criterion
+                .as_deref()
+                .is_none_or(|criterion| value == Some(criterion))
+        };
+        exact(&self.app_id, window.app_id)
+            && exact(&self.class, window.class)
+            && self.title.as_deref().is_none_or(|pattern| {
+                window
+                    .title
+                    .is_some_and(|title| glob(&pattern.chars().collect::<Vec<_>>(), title))
+            })
+    }
+}
+
+fn glob(pattern: &[char], text: &str) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => text
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain([text.len()])
+            .any(|index| glob(rest, &text[index..])),
+        Some((first, rest)) => {
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(c) if *first == '?' || c == *first => glob(rest, chars.as_str()),
+                _ => false,
+            }
+        }
+    }
+}
+
+/// A slot of the tree kept for a window which comes later, like the swallows of i3.
+///
+/// The slot is a window in the tree with an id which is not used by any real window, so it
+/// keeps its place and percent. It is never dispatched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    /// The id of the window which keeps the slot
+    pub slot: Id,
+    pub criteria: Criteria,
+    /// When it is removed by [`TopElementMap::expire_placeholders`]
+    pub expires: Option<Instant>,
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// The placeholders waiting for windows
+    pub fn placeholders(&self) -> &[Placeholder] {
+        &self.placeholders
+    }
+
+    /// Make the window `slot` a placeholder, it is swallowed by the first window which matches
+    /// the criteria. With a `timeout` it is removed by [`TopElementMap::expire_placeholders`]
+    /// after that time.
+    /// It only fails when the slot id is not found
+    pub fn add_placeholder(
+        &mut self,
+        slot: Id,
+        criteria: Criteria,
+        timeout: Option<Duration>,
+    ) -> Result<()> {
+        if self.root.find_window(slot).is_none() {
+            return Err(Error::ElementNotFound);
+        }
+        self.placeholders
+            .retain(|placeholder| placeholder.slot != slot);
+        self.placeholders.push(Placeholder {
+            slot,
+            criteria,
+            expires: timeout.map(|timeout| Instant::now() + timeout),
+        });
+        Ok(())
+    }
+
+    /// Put the new window in the first placeholder of the tree which it matches, it takes the
+    /// size, position and percent of the slot. It returns `false` when no placeholder matches,
+    /// then the window should be inserted as usual.
+    /// It only fails when the id is already in the map
+    pub fn swallow<F>(&mut self, id: Id, window: &WindowProps, f: &mut F) -> Result<bool>
+    where
+        F: DispatchCallback<T>,
+    {
+        if self.root.find_window(id).is_some() {
+            return Err(Error::DuplicateId);
+        }
+        let Some(slot) = self.root.windows().into_iter().find_map(|(slot, _)| {
+            self.placeholders
+                .iter()
+                .any(|placeholder| placeholder.slot == slot && placeholder.criteria.matches(window))
+                .then_some(slot)
+        }) else {
+            return Ok(false);
+        };
+        self.placeholders
+            .retain(|placeholder| placeholder.slot != slot);
+        let f = &mut self.view(f);
+        replace_id(&mut self.root, slot, id);
+        if let Some(auto) = &mut self.auto {
+            for o_id in auto.windows.iter_mut().filter(|o_id| **o_id == slot) {
+                *o_id = id;
+            }
+            if let Some(manual) = &mut auto.manual {
+                replace_id(manual, slot, id);
+            }
+            for placement in auto.placements.iter_mut().flatten() {
+                if placement.id == slot {
+                    placement.id = id;
+                }
+            }
+        }
+        if let Some(window) = self.root.find_window(id) {
+            f.callback(id, window.size_pos());
+        }
+        Ok(true)
+    }
+
+    /// Remove a placeholder and its slot from the tree
+    /// It only fails when the slot is not a placeholder
+    pub fn remove_placeholder<F>(&mut self, slot: Id, f: &mut F) -> Result<()>
+    where
+        F: DispatchCallback<T>,
+    {
+        let Some(index) = self
+            .placeholders
+            .iter()
+            .position(|placeholder| placeholder.slot == slot)
+        else {
+            return Err(Error::ElementNotFound);
+        };
+        self.placeholders.remove(index);
+        self.delete(slot, f)
+    }
+
+    /// Remove every placeholder whose time is over at `now`
+    pub fn expire_placeholders<F>(&mut self, now: Instant, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let expired: Vec<Id> = self
+            .placeholders
+            .iter()
+            .filter(|placeholder| placeholder.expires.is_some_and(|expires| expires <= now))
+            .map(|placeholder| placeholder.slot)
+            .collect();
+        for slot in expired {
+            let _ = self.remove_placeholder(slot, f);
+        }
+    }
+
+    /// Remove every placeholder
+    pub fn clear_placeholders<F>(&mut self, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        for slot in self
+            .placeholders
+            .iter()
+            .map(|placeholder| placeholder.slot)
+            .collect::<Vec<_>>()
+        {
+            let _ = self.remove_placeholder(slot, f);
+        }
+    }
+}
+
+fn replace_id<T: MinusAbleMatUnit>(root: &mut Element<T>, slot: Id, new_id: Id) {
+    if let Some(Element::Window { id, .. }) = root.find_window_mut(slot) {
+        *id = new_id;
+    }
+}
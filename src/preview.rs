@@ -0,0 +1,48 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{Direction, Id, Result, SizeAndPos, TopElementMap};
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// The windows which would be dispatched by [`TopElementMap::insert`], the map is not changed.
+    /// It fails with the error the insert would return
+    pub fn preview_insert(
+        &self,
+        id: Id,
+        target: Id,
+        direction: Direction,
+    ) -> Result<Vec<(Id, SizeAndPos<T>)>> {
+        self.preview(|map| map.insert(id, target, direction, &mut ()))
+    }
+
+    /// The windows which would be dispatched by [`TopElementMap::drag_and_drop`], the map is not
+    /// changed. It fails with the error the drag and drop would return
+    pub fn preview_drag_and_drop(
+        &self,
+        id: Id,
+        target: Id,
+        direction: Direction,
+    ) -> Result<Vec<(Id, SizeAndPos<T>)>> {
+        self.preview(|map| map.drag_and_drop(id, target, direction, &mut ()))
+    }
+
+    /// The windows which would be dispatched by [`TopElementMap::drag_resize`], the map is not
+    /// changed. It fails with the error the resize would return
+    pub fn preview_drag_resize(
+        &self,
+        transfer: T,
+        direction: Direction,
+        target: Id,
+    ) -> Result<Vec<(Id, SizeAndPos<T>)>> {
+        self.preview(|map| map.drag_resize(transfer, direction, target, &mut ()))
+    }
+
+    /// Run the real operation on a copy of the map, so the preview is always what it does
+    fn preview(
+        &self,
+        op: impl FnOnce(&mut Self) -> Result<()>,
+    ) -> Result<Vec<(Id, SizeAndPos<T>)>> {
+        let mut map = self.clone_without_history();
+        let mut changes = Vec::new();
+        map.transaction(&mut |id, size_pos| changes.push((id, size_pos)), op)?;
+        Ok(changes)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{Position, TopElementMap};
+
+/// The characters used by [`TopElementMap::render`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderStyle {
+    /// `-` and `|`, like the diagrams in the docs
+    #[default]
+    Ascii,
+    /// The box drawing characters, like `┌─┬─┐`
+    Unicode,
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 1 << 1;
+const LEFT: u8 = 1 << 2;
+const RIGHT: u8 = 1 << 3;
+
+impl RenderStyle {
+    fn border(&self, lines: u8) -> char {
+        match self {
+            Self::Ascii if lines & (UP | DOWN) == UP | DOWN => '|',
+            Self::Ascii if lines & (LEFT | RIGHT) != 0 => '-',
+            Self::Ascii => '|',
+            // NOTE: the bits are RIGHT, LEFT, DOWN and UP
+            Self::Unicode => match lines {
+                0b0001..=0b0011 => '│',
+                0b1100 | 0b0100 | 0b1000 => '─',
+                0b1010 => '┌',
+                0b0110 => '┐',
+                0b1001 => '└',
+                0b0101 => '┘',
+                0b1011 => '├',
+                0b0111 => '┤',
+                0b1110 => '┬',
+                0b1101 => '┴',
+                _ => '┼',
+            },
+        }
+    }
+}
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// Draw the map in a grid of `columns` and `rows` characters, every visible window is a box
+    /// with its id in the center.
+    ///
+    /// The map is scaled to the grid, so the borders of the windows side by side are shared.
+    /// When the id does not fit in a small box, `#` is drawn instead, and a box without space
+    /// inside only has its borders. The hidden windows, like the ones behind the focused window
+    /// in the monocle mode, and the parts out of the map are not drawn.
+    pub fn render(&self, columns: usize, rows: usize, style: RenderStyle) -> String {
+        let (columns, rows) = (columns.max(2), rows.max(2));
+        let whole = self.root.size_pos();
+        let scale = |position: Position<T>| {
+            let x = (position.x - whole.position.x).to_f32() / whole.size.width.to_f32();
+            let y = (position.y - whole.position.y).to_f32() / whole.size.height.to_f32();
+            let cell = |ratio: f32, count: usize| {
+                (ratio * (count - 1) as f32)
+                    .round()
+                    .clamp(0., (count - 1) as f32) as usize
+            };
+            (cell(x, columns), cell(y, rows))
+        };
+
+        let mut lines = vec![vec![0u8; columns]; rows];
+        let mut labels: Vec<(usize, usize, Vec<char>)> = Vec::new();
+        let mut boxes = vec![((0, 0), (columns - 1, rows - 1), None)];
+        for placement in self.placements() {
+            if !placement.visible {
+                continue;
+            }
+            let size_pos = placement.size_pos;
+            let start = scale(size_pos.position);
+            let end = scale(Position {
+                x: size_pos.position.x + size_pos.size.width,
+                y: size_pos.position.y + size_pos.size.height,
+            });
+            boxes.push((start, end, Some(placement.id)));
+        }
+        for ((x0, y0), (x1, y1), id) in boxes {
+            for (x, cells) in lines[y0].iter_mut().enumerate().take(x1 + 1).skip(x0) {
+                *cells |= if x > x0 { LEFT } else { 0 } | if x < x1 { RIGHT } else { 0 };
+            }
+            for (x, cells) in lines[y1].iter_mut().enumerate().take(x1 + 1).skip(x0) {
+                *cells |= if x > x0 { LEFT } else { 0 } | if x < x1 { RIGHT } else { 0 };
+            }
+            for (y, line) in lines.iter_mut().enumerate().take(y1 + 1).skip(y0) {
+                let vertical = if y > y0 { UP } else { 0 } | if y < y1 { DOWN } else { 0 };
+                line[x0] |= vertical;
+                line[x1] |= vertical;
+            }
+            let Some(id) = id else {
+                continue;
+            };
+            // NOTE: the space inside the borders
+            let (width, height) = (x1.saturating_sub(x0 + 1), y1.saturating_sub(y0 + 1));
+            if width == 0 || height == 0 {
+                continue;
+            }
+            let text: Vec<char> = id.0.to_string().chars().collect();
+            let text = if text.len() <= width { text } else { vec!['#'] };
+            let x = x0 + 1 + (width - text.len()) / 2;
+            let y = y0 + 1 + (height - 1) / 2;
+            labels.push((x, y, text));
+        }
+
+        let mut grid: Vec<Vec<char>> = lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|cells| {
+                        if *cells == 0 {
+                            ' '
+                        } else {
+                            style.border(*cells)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        for (x, y, text) in labels {
+            for (offset, c) in text.into_iter().enumerate() {
+                grid[y][x + offset] = c;
+            }
+        }
+        grid.into_iter()
+            .map(|line| line.into_iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashSet;
+
+use crate::utils::MinusAbleMatUnit;
+use crate::{Element, Error, Id, InsertWay, Result, SizeAndPos};
+
+/// The version of the format a [`TopElementMap`](crate::TopElementMap) is saved in. The version 2
+/// added the placeholders, the maps saved in the version 1 are still read.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// How far the shares of a container can be from the whole
+const PERCENT_TOLERANCE: f32 = 1e-3;
+/// How far a child can be out of its container, in the unit of the map
+const POSITION_TOLERANCE: f32 = 1.;
+
+impl<T: MinusAbleMatUnit> Element<T> {
+    /// Check the tree can be used by the map. The windows are unique, the containers are not
+    /// empty, the shares of every container add up to the whole, the children are inside their
+    /// container, and every window is where its percent puts it, so they do not overlap.
+    pub fn validate(&self) -> Result<()> {
+        if let Self::EmptyOutput(size_pos) = self {
+            return check_size(*size_pos);
+        }
+        self.validate_element(&mut HashSet::new())?;
+        let mut remapped = self.clone();
+        remapped.remap(self.size_pos(), &mut ());
+        let misplaced = self
+            .windows()
+            .into_iter()
+            .zip(remapped.windows())
+            .any(|((_, size_pos), (_, expected))| !same_place(size_pos, expected));
+        if misplaced {
+            return Err(Error::InvalidLayout(
+                "a window is not where its percent puts it",
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_element(&self, ids: &mut HashSet<Id>) -> Result<()> {
+        check_size(self.size_pos())?;
+        let percent = self.percent();
+        if !(percent.width > 0.
+            && percent.width <= 1.
+            && percent.height > 0.
+            && percent.height <= 1.)
+        {
+            return Err(Error::InvalidLayout("a percent is out of range"));
+        }
+        let (way, elements) = match self {
+            Self::EmptyOutput(_) => {
+                return Err(Error::InvalidLayout("an empty output is in a container"));
+            }
+            Self::Window { id, .. } => {
+                if !ids.insert(*id) {
+                    return Err(Error::InvalidLayout("a window id is used twice"));
+                }
+                return Ok(());
+            }
+            Self::Vertical { elements, .. } => (InsertWay::Vertical, elements),
+            Self::Horizontal { elements, .. } => (InsertWay::Horizontal, elements),
+        };
+        if elements.is_empty() {
+            return Err(Error::InvalidLayout("a container is empty"));
+        }
+        let other_way = match way {
+            InsertWay::Vertical => InsertWay::Horizontal,
+            InsertWay::Horizontal => InsertWay::Vertical,
+        };
+        let mut total = 0.;
+        for element in elements {
+            let percent = element.percent();
+            if (percent.on_axis(other_way) - 1.).abs() > PERCENT_TOLERANCE {
+                return Err(Error::InvalidLayout("a child does not fill its container"));
+            }
+            total += percent.on_axis(way);
+            if !contains(self.size_pos(), element.size_pos()) {
+                return Err(Error::InvalidLayout("a child is out of its container"));
+            }
+            element.validate_element(ids)?;
+        }
+        if (total - 1.).abs() > PERCENT_TOLERANCE {
+            return Err(Error::InvalidLayout(
+                "the shares of a container are not the whole",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn check_size<T: MinusAbleMatUnit>(size_pos: SizeAndPos<T>) -> Result<()> {
+    let size = size_pos.size;
+    if size.width < T::zero() || size.height < T::zero() {
+        return Err(Error::InvalidLayout("a size is minus"));
+    }
+    Ok(())
+}
+
+fn same_place<T: MinusAbleMatUnit>(one: SizeAndPos<T>, other: SizeAndPos<T>) -> bool {
+    let close = |one: T, other: T| (one.to_f32() - other.to_f32()).abs() <= POSITION_TOLERANCE;
+    close(one.position.x, other.position.x)
+        && close(one.position.y, other.position.y)
+        && close(one.size.width, other.size.width)
+        && close(one.size.height, other.size.height)
+}
+
+fn contains<T: MinusAbleMatUnit>(outer: SizeAndPos<T>, inner: SizeAndPos<T>) -> bool {
+    let start =
+        |size_pos: SizeAndPos<T>| (size_pos.position.x.to_f32(), size_pos.position.y.to_f32());
+    let end = |size_pos: SizeAndPos<T>| {
+        let (x, y) = start(size_pos);
+        (
+            x + size_pos.size.width.to_f32(),
+            y + size_pos.size.height.to_f32(),
+        )
+    };
+    let (outer_start, outer_end) = (start(outer), end(outer));
+    let (inner_start, inner_end) = (start(inner), end(inner));
+    inner_start.0 >= outer_start.0 - POSITION_TOLERANCE
+        && inner_start.1 >= outer_start.1 - POSITION_TOLERANCE
+        && inner_end.0 <= outer_end.0 + POSITION_TOLERANCE
+        && inner_end.1 <= outer_end.1 + POSITION_TOLERANCE
+}
+
+#[cfg(feature = "serde")]
+mod format {
+    use std::fmt;
+    use std::marker::PhantomData;
+    use std::time::{Duration, Instant};
+
+    use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+    use serde::de::{self, MapAccess, SeqAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, ser};
+
+    use super::FORMAT_VERSION;
+    use crate::layout::{Autotiling, InsertPolicy};
+    use crate::utils::MinusAbleMatUnit;
+    use crate::{Criteria, Element, Error, Id, Placeholder, TopElementMap};
+
+    /// The map as it is saved. The automatic layout is not saved, the tree it built is restored
+    /// as a tree edited by hand, set the layout again after loading. The `version` is the first
+    /// field, so a newer format is known before the rest is read.
+    #[derive(Serialize)]
+    struct SnapshotRef<'a, T: MinusAbleMatUnit> {
+        version: u32,
+        root: &'a Element<T>,
+        auto_normalize: bool,
+        insert_policy: InsertPolicy,
+        autotiling: Option<Autotiling<T>>,
+        focused: Option<Id>,
+        monocle: bool,
+        placeholders: Vec<SavedPlaceholder>,
+    }
+
+    /// The time left of a placeholder is saved, an [`Instant`] means nothing after a restart
+    #[derive(Serialize, Deserialize)]
+    struct SavedPlaceholder {
+        slot: Id,
+        criteria: Criteria,
+        timeout: Option<Duration>,
+    }
+
+    /// The fields after the `version`
+    #[derive(Deserialize)]
+    struct Snapshot<T: MinusAbleMatUnit> {
+        root: Element<T>,
+        auto_normalize: bool,
+        insert_policy: InsertPolicy,
+        autotiling: Option<Autotiling<T>>,
+        focused: Option<Id>,
+        monocle: bool,
+        #[serde(default)]
+        placeholders: Vec<SavedPlaceholder>,
+    }
+
+    const FIELDS: &[&str] = &[
+        "version",
+        "root",
+        "auto_normalize",
+        "insert_policy",
+        "autotiling",
+        "focused",
+        "monocle",
+        "placeholders",
+    ];
+
+    impl<T> Serialize for TopElementMap<T>
+    where
+        T: MinusAbleMatUnit + Serialize,
+    {
+        /// It fails when the windows are placed by a layout, the tree does not show where they
+        /// are then
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if self.auto.as_ref().is_some_and(|auto| auto.is_placed()) {
+                return Err(ser::Error::custom(Error::InvalidLayout(
+                    "the windows are placed by a layout, not by the tree",
+                )));
+            }
+            let now = Instant::now();
+            SnapshotRef {
+                version: FORMAT_VERSION,
+                root: &self.root,
+                auto_normalize: self.auto_normalize,
+                insert_policy: self.insert_policy,
+                autotiling: self.autotiling,
+                focused: self.focused,
+                monocle: self.monocle,
+                placeholders: self
+                    .placeholders
+                    .iter()
+                    .map(|placeholder| SavedPlaceholder {
+                        slot: placeholder.slot,
+                        criteria: placeholder.criteria.clone(),
+                        timeout: placeholder
+                            .expires
+                            .map(|expires| expires.saturating_duration_since(now)),
+                    })
+                    .collect(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    struct SnapshotVisitor<T>(PhantomData<T>);
+
+    fn check_version<E: de::Error>(version: u32) -> Result<(), E> {
+        if !(1..=FORMAT_VERSION).contains(&version) {
+            return Err(de::Error::custom(Error::UnsupportedVersion(version)));
+        }
+        Ok(())
+    }
+
+    impl<'de, T> Visitor<'de> for SnapshotVisitor<T>
+    where
+        T: MinusAbleMatUnit + Deserialize<'de>,
+    {
+        type Value = Snapshot<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a saved map which starts with its version")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            if map.next_key::<String>()?.as_deref() != Some("version") {
+                return Err(de::Error::missing_field("version"));
+            }
+            check_version(map.next_value()?)?;
+            Snapshot::deserialize(MapAccessDeserializer::new(map))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let Some(version) = seq.next_element()? else {
+                return Err(de::Error::invalid_length(0, &self));
+            };
+            check_version(version)?;
+            Snapshot::deserialize(SeqAccessDeserializer::new(seq))
+        }
+    }
+
+    impl<'de, T> Deserialize<'de> for TopElementMap<T>
+    where
+        T: MinusAbleMatUnit + Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let snapshot = deserializer.deserialize_struct(
+                "Snapshot",
+                FIELDS,
+                SnapshotVisitor(PhantomData),
+            )?;
+            snapshot.root.validate().map_err(de::Error::custom)?;
+            if let Some(focused) = snapshot.focused
+                && snapshot.root.find_window(focused).is_none()
+            {
+                return Err(de::Error::custom(Error::InvalidLayout(
+                    "the focused window is not in the tree",
+                )));
+            }
+            for (index, placeholder) in snapshot.placeholders.iter().enumerate() {
+                if snapshot.root.find_window(placeholder.slot).is_none()
+                    || snapshot.placeholders[..index]
+                        .iter()
+                        .any(|other| other.slot == placeholder.slot)
+                {
+                    return Err(de::Error::custom(Error::InvalidLayout(
+                        "a placeholder is not a window of the tree, or it is saved twice",
+                    )));
+                }
+            }
+            let now = Instant::now();
+            let mut map = TopElementMap::new(snapshot.root.size_pos());
+            map.root = snapshot.root;
+            map.auto_normalize = snapshot.auto_normalize;
+            map.insert_policy = snapshot.insert_policy;
+            map.autotiling = snapshot.autotiling;
+            map.focused = snapshot.focused;
+            map.monocle = snapshot.monocle;
+            map.placeholders = snapshot
+                .placeholders
+                .into_iter()
+                .map(|placeholder| Placeholder {
+                    slot: placeholder.slot,
+                    criteria: placeholder.criteria,
+                    expires: placeholder.timeout.map(|timeout| now + timeout),
+                })
+                .collect();
+            for (id, _) in map.root.windows() {
+                id.reserve();
+            }
+            Ok(map)
+        }
+    }
+}
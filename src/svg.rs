@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+use crate::utils::MinusAbleMatUnit;
+use crate::{Element, Id, InsertWay, SizeAndPos, TopElementMap};
+
+/// How far a nested container is drawn inside its parent, so their borders are not on each other
+const NEST_INSET: f32 = 3.;
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// Draw the map as a SVG picture at its real size and position.
+    ///
+    /// Every visible window is a box with its id and its percent in the container, the focused
+    /// one has a red border. The containers are dashed boxes with their way, `H →` or `V ↓`,
+    /// they are not drawn in the monocle mode.
+    /// When the [`Autotiling`](crate::layout::Autotiling) is turned on, the space of its gaps
+    /// and borders is drawn in every window.
+    pub fn to_svg(&self) -> String {
+        let whole = self.root.size_pos();
+        let (x, y, width, height) = rect(whole);
+        let font = height.min(width) / 40.;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{x} {y} {width} {height}" width="{width}" height="{height}" font-family="monospace" font-size="{font}">"#
+        );
+        let _ = writeln!(
+            svg,
+            r##"<rect class="output" x="{x}" y="{y}" width="{width}" height="{height}" fill="#f3f4f6"/>"##
+        );
+
+        let mut percents = Vec::new();
+        collect_percents(&self.root, None, &mut percents);
+        for placement in self.placements() {
+            if !placement.visible {
+                continue;
+            }
+            let (x, y, width, height) = rect(placement.size_pos);
+            let focused = self.focused == Some(placement.id);
+            let (class, stroke, stroke_width) = if focused {
+                ("window focused", "#dc2626", 4.)
+            } else {
+                ("window", "#1e3a8a", 2.)
+            };
+            let _ = writeln!(
+                svg,
+                r##"<rect class="{class}" x="{x}" y="{y}" width="{width}" height="{height}" fill="#dbeafe" stroke="{stroke}" stroke-width="{stroke_width}"/>"##
+            );
+            if let Some(autotiling) = self.autotiling {
+                let inset_x = (autotiling.gaps.width + autotiling.border).to_f32();
+                let inset_y = (autotiling.gaps.height + autotiling.border).to_f32();
+                let _ = writeln!(
+                    svg,
+                    r##"<rect class="gap" x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#9ca3af" stroke-dasharray="2 2"/>"##,
+                    x + inset_x,
+                    y + inset_y + autotiling.title.to_f32(),
+                    (width - inset_x * 2.).max(0.),
+                    (height - inset_y * 2. - autotiling.title.to_f32()).max(0.),
+                );
+            }
+            let percent = percents
+                .iter()
+                .find(|(id, _)| *id == placement.id)
+                .map_or(100., |(_, percent)| percent * 100.);
+            let (center_x, center_y) = (x + width / 2., y + height / 2.);
+            let _ = writeln!(
+                svg,
+                r#"<text x="{center_x}" y="{center_y}" text-anchor="middle">Id {}</text>"#,
+                placement.id.0
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{center_x}" y="{}" text-anchor="middle">{}%</text>"#,
+                center_y + font * 1.2,
+                round(percent)
+            );
+        }
+        // NOTE: the tree of a layout which only places the windows is not what is seen
+        let placed = self.auto.as_ref().is_some_and(|auto| auto.is_placed());
+        if !self.monocle && !placed {
+            draw_containers(&self.root, 0, font, &mut svg);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn rect<T: MinusAbleMatUnit>(size_pos: SizeAndPos<T>) -> (f32, f32, f32, f32) {
+    (
+        size_pos.position.x.to_f32(),
+        size_pos.position.y.to_f32(),
+        size_pos.size.width.to_f32(),
+        size_pos.size.height.to_f32(),
+    )
+}
+
+/// Keep two decimals, so the labels are short
+fn round(value: f32) -> f32 {
+    (value * 100.).round() / 100.
+}
+
+/// The percent of every window on the way of its container
+fn collect_percents<T: MinusAbleMatUnit>(
+    element: &Element<T>,
+    way: Option<InsertWay>,
+    percents: &mut Vec<(Id, f32)>,
+) {
+    match element {
+        Element::EmptyOutput(_) => {}
+        Element::Window { id, percent, .. } => {
+            percents.push((*id, way.map_or(1., |way| percent.on_axis(way))));
+        }
+        Element::Vertical { elements, .. } | Element::Horizontal { elements, .. } => {
+            for child in elements {
+                collect_percents(child, element.way(), percents);
+            }
+        }
+    }
+}
+
+fn draw_containers<T: MinusAbleMatUnit>(
+    element: &Element<T>,
+    depth: usize,
+    font: f32,
+    svg: &mut String,
+) {
+    let (Element::Vertical { elements, .. } | Element::Horizontal { elements, .. }) = element
+    else {
+        return;
+    };
+    let (x, y, width, height) = rect(element.size_pos());
+    let inset = NEST_INSET * depth as f32;
+    let label = match element.way() {
+        Some(InsertWay::Vertical) => "V ↓",
+        _ => "H →",
+    };
+    let _ = writeln!(
+        svg,
+        r##"<rect class="container" x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#059669" stroke-dasharray="8 4"/>"##,
+        x + inset,
+        y + inset,
+        (width - inset * 2.).max(0.),
+        (height - inset * 2.).max(0.),
+    );
+    let _ = writeln!(
+        svg,
+        r##"<text class="way" x="{}" y="{}" fill="#059669">{label}</text>"##,
+        x + inset + font / 2.,
+        y + inset + font * 1.2,
+    );
+    for child in elements {
+        draw_containers(child, depth + 1, font, svg);
+    }
+}
@@ -1,4 +1,9 @@
 use super::*;
+use crate::layout::{
+    Autotiling, CenteredMaster, Dwindle, Grid, InsertPolicy, LayoutPolicy, MasterStack, Placement,
+    Scrolling,
+};
+use std::time::{Duration, Instant};
 const DISPLAY_SIZE: SizeAndPos = SizeAndPos {
     size: Size {
         width: 1980.,
@@ -811,3 +816,1856 @@ fn swap_test() {
         })
         .expect("Should ok");
 }
+
+#[test]
+fn balance_test() {
+    // ------------
+    // |    |  1  |
+    // | 0  |-----|
+    // |    | 2|3 |
+    // ------------
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(2), Id(1), InsertWay::Vertical, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(3), Id(2), InsertWay::Horizontal, &mut |_, _| {});
+
+    // Only the subtree of 2 and 3, it is already balanced
+    element_map
+        .balance_container(Id(2), &mut |_, _| unreachable!())
+        .expect("Should ok");
+    assert!(element_map.balance_container(Id(4), &mut ()).is_err());
+
+    let mut times = 0;
+    element_map
+        .balance(&mut |id, size_pos: SizeAndPos| {
+            times += 1;
+            match id {
+                Id(0) => assert_eq!(size_pos.size.width, 495.),
+                Id(1) => assert_eq!(size_pos.size.width, 1485.),
+                Id(2) | Id(3) => assert_eq!(size_pos.size.width, 742.5),
+                _ => unreachable!(),
+            }
+            assert!((size_pos.size.width * size_pos.size.height - 1980. * 1080. / 4.).abs() < 1.);
+        })
+        .expect("Should ok");
+    assert_eq!(times, 4);
+}
+
+#[test]
+fn toggle_split_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Vertical, &mut |_, _| {});
+    assert!(matches!(
+        element_map.toggle_split(Id(0), &mut ()),
+        Err(Error::ContainerNotFound)
+    ));
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Vertical, &mut |_, _| {});
+    let _ = element_map.drag_resize(-270., Direction::Bottom, Id(0), &mut |_, _| {});
+    // ------------
+    // |    0     |
+    // ------------
+    // |          |
+    // |    1     |
+    // |          |
+    // ------------
+    let mut times = 0;
+    element_map
+        .toggle_split(Id(1), &mut |id, size_pos| {
+            times += 1;
+            match id {
+                Id(0) => assert_eq!(
+                    size_pos,
+                    SizeAndPos {
+                        size: Size {
+                            width: 495.,
+                            height: 1080.
+                        },
+                        position: Position { x: 0., y: 0. }
+                    }
+                ),
+                Id(1) => assert_eq!(
+                    size_pos,
+                    SizeAndPos {
+                        size: Size {
+                            width: 1485.,
+                            height: 1080.
+                        },
+                        position: Position { x: 495., y: 0. }
+                    }
+                ),
+                _ => unreachable!(),
+            }
+        })
+        .expect("Should ok");
+    assert_eq!(times, 2);
+    element_map
+        .set_split(Id(0), InsertWay::Horizontal, &mut |_, _| unreachable!())
+        .expect("Should ok");
+    assert!(
+        element_map
+            .set_split(Id(2), InsertWay::Vertical, &mut ())
+            .is_err()
+    );
+}
+
+#[test]
+fn wrap_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    assert!(element_map.wrap(Id(1), InsertWay::Vertical).is_err());
+    element_map
+        .wrap(Id(0), InsertWay::Vertical)
+        .expect("Should ok");
+    // The container is vertical, so the horizontal insert follows it
+    // ------------
+    // |    0     |
+    // ------------
+    // |    1     |
+    // ------------
+    element_map
+        .insert_new(
+            Id(1),
+            Id(0),
+            InsertWay::Horizontal,
+            &mut |id, size_pos: SizeAndPos| {
+                assert_eq!(size_pos.size.width, 1980.);
+                assert_eq!(size_pos.size.height, 540.);
+                match id {
+                    Id(0) => assert_eq!(size_pos.position.y, 0.),
+                    Id(1) => assert_eq!(size_pos.position.y, 540.),
+                    _ => unreachable!(),
+                }
+            },
+        )
+        .expect("Should ok");
+    // The pinned container is not collapsed
+    element_map
+        .delete(Id(1), &mut |id, size_pos| {
+            assert_eq!(id, Id(0));
+            assert_eq!(size_pos, DISPLAY_SIZE);
+        })
+        .expect("Should ok");
+    assert!(matches!(element_map.root, Element::Vertical { .. }));
+    element_map
+        .insert_new(
+            Id(2),
+            Id(0),
+            InsertWay::Horizontal,
+            &mut |_, size_pos: SizeAndPos| {
+                assert_eq!(size_pos.size.height, 540.);
+            },
+        )
+        .expect("Should ok");
+    let _ = element_map.delete(Id(2), &mut |_, _| {});
+
+    // It disappears with the last window
+    element_map
+        .delete(Id(0), &mut |_, _| unreachable!())
+        .expect("Should ok");
+    assert!(matches!(element_map.root, Element::EmptyOutput(_)));
+
+    // A pinned container in a container disappears with its last window too
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.wrap(Id(1), InsertWay::Vertical);
+    element_map
+        .delete(Id(1), &mut |id, size_pos| {
+            assert_eq!(id, Id(0));
+            assert_eq!(size_pos, DISPLAY_SIZE);
+        })
+        .expect("Should ok");
+    assert!(element_map.root.is_window());
+}
+
+#[test]
+fn normalize_test() {
+    // --------------------
+    // |     |     2      |
+    // |  0  |------------|
+    // |     |  1  |  3   |
+    // --------------------
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert(Id(2), Id(1), Direction::Top, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(3), Id(1), InsertWay::Horizontal, &mut |_, _| {});
+    // delete 2, so H[0, H[1, 3]] is left
+    let _ = element_map.delete(Id(2), &mut |_, _| {});
+    let before = element_map.root().windows();
+
+    element_map.normalize().expect("Should ok");
+    let Element::Horizontal { elements, .. } = element_map.root() else {
+        panic!("Should be horizontal");
+    };
+    assert_eq!(elements.len(), 3);
+    let percents: Vec<f32> = elements.iter().map(|e| e.percent().width).collect();
+    assert_eq!(percents, vec![0.5, 0.25, 0.25]);
+    assert_eq!(element_map.root().windows(), before);
+
+    // The remap still gives the same geometry
+    element_map.remap(DISPLAY_SIZE, &mut |id, size_pos| {
+        assert!(before.contains(&(id, size_pos)));
+    });
+
+    // With auto normalize, the tree is flattened after delete
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_auto_normalize(true);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert(Id(2), Id(1), Direction::Top, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(3), Id(1), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.delete(Id(2), &mut |_, _| {});
+    let Element::Horizontal { elements, .. } = element_map.root() else {
+        panic!("Should be horizontal");
+    };
+    assert!(elements.iter().all(Element::is_window));
+}
+
+#[test]
+fn rotate_mirror_transpose_test() {
+    // ----------------
+    // |     |   1    |
+    // |  0  |--------|
+    // |     |   2    |
+    // ----------------
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(2), Id(1), InsertWay::Vertical, &mut |_, _| {});
+    let _ = element_map.drag_resize(-495., Direction::Right, Id(0), &mut |_, _| {});
+    let origin = element_map.root().windows();
+
+    // ----------------
+    // |      0       |
+    // |--------------|
+    // |  2  |   1    |
+    // ----------------
+    element_map
+        .rotate(Rotation::Deg90, &mut |id, size_pos| match id {
+            Id(0) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 1980.,
+                        height: 270.
+                    },
+                    position: Position { x: 0., y: 0. }
+                }
+            ),
+            Id(1) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 990.,
+                        height: 810.
+                    },
+                    position: Position { x: 990., y: 270. }
+                }
+            ),
+            Id(2) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 990.,
+                        height: 810.
+                    },
+                    position: Position { x: 0., y: 270. }
+                }
+            ),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    element_map
+        .rotate(Rotation::Deg270, &mut |_, _| {})
+        .expect("Should ok");
+    assert_eq!(element_map.root().windows(), origin);
+
+    // Mirror twice gives the origin back
+    element_map
+        .mirror(
+            InsertWay::Horizontal,
+            &mut |id, size_pos: SizeAndPos| match id {
+                Id(0) => assert_eq!(size_pos.position.x, 1485.),
+                Id(1) | Id(2) => assert_eq!(size_pos.position.x, 0.),
+                _ => unreachable!(),
+            },
+        )
+        .expect("Should ok");
+    element_map
+        .mirror(InsertWay::Horizontal, &mut |_, _| {})
+        .expect("Should ok");
+    assert_eq!(element_map.root().windows(), origin);
+
+    // Only 1 and 2 are moved in the container
+    let mut times = 0;
+    element_map
+        .transpose_container(Id(1), &mut |id, size_pos: SizeAndPos| {
+            times += 1;
+            match id {
+                Id(1) => assert_eq!(size_pos.position.x, 495.),
+                Id(2) => assert_eq!(size_pos.position.x, 495. + 742.5),
+                _ => unreachable!(),
+            }
+        })
+        .expect("Should ok");
+    assert_eq!(times, 2);
+    assert!(
+        element_map
+            .rotate_container(Id(0), Rotation::Deg180, &mut ())
+            .is_ok()
+    );
+    assert!(
+        element_map
+            .mirror_container(Id(3), InsertWay::Vertical, &mut ())
+            .is_err()
+    );
+}
+
+#[test]
+fn master_stack_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    assert!(matches!(
+        element_map.set_master_count(2, &mut ()),
+        Err(Error::LayoutNotActive)
+    ));
+    // The tree is already a master-stack
+    element_map.set_layout(
+        Some(Box::new(MasterStack::default())),
+        &mut |_, _| unreachable!(),
+    );
+    // --------------------
+    // |        |   1     |
+    // |   0    |---------|
+    // |        |   2     |
+    // --------------------
+    element_map
+        .push(Id(2), &mut |id, size_pos| match id {
+            Id(1) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 990.,
+                        height: 540.
+                    },
+                    position: Position { x: 990., y: 0. }
+                }
+            ),
+            Id(2) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 990.,
+                        height: 540.
+                    },
+                    position: Position { x: 990., y: 540. }
+                }
+            ),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    element_map
+        .set_master_ratio(0.75, &mut |id, size_pos: SizeAndPos| match id {
+            Id(0) => assert_eq!(size_pos.size.width, 1485.),
+            Id(1) | Id(2) => assert_eq!(size_pos.size.width, 495.),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    element_map
+        .promote(Id(2), &mut |id, size_pos: SizeAndPos| match id {
+            Id(2) => assert_eq!(size_pos.position, Position { x: 0., y: 0. }),
+            Id(0) => assert_eq!(size_pos.position, Position { x: 1485., y: 0. }),
+            Id(1) => assert_eq!(size_pos.position, Position { x: 1485., y: 540. }),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    assert_eq!(
+        element_map.layout_windows(),
+        Some(&[Id(2), Id(0), Id(1)][..])
+    );
+    element_map
+        .set_master_side(Direction::Top, &mut ())
+        .expect("Should ok");
+    element_map
+        .swap(Id(2), Id(1), &mut |id, size_pos: SizeAndPos| match id {
+            Id(1) => assert_eq!(size_pos.size.height, 810.),
+            Id(2) => assert_eq!(size_pos.size.height, 270.),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    // Only the master is left
+    element_map
+        .delete(Id(0), &mut |_, _| {})
+        .expect("Should ok");
+    element_map
+        .delete(Id(2), &mut |id, size_pos| {
+            assert_eq!(id, Id(1));
+            assert_eq!(size_pos, DISPLAY_SIZE);
+        })
+        .expect("Should ok");
+    assert!(element_map.delete(Id(2), &mut ()).is_err());
+}
+
+#[test]
+fn dwindle_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_insert_policy(InsertPolicy::Dwindle(Dwindle {
+        ratio: 0.75,
+        ..Default::default()
+    }));
+    element_map.push(Id(0), &mut |_, _| {}).expect("Should ok");
+    // --------------------
+    // |          |       |
+    // |    0     |   1   |
+    // |          |       |
+    // --------------------
+    element_map
+        .push(Id(1), &mut |id, size_pos| match id {
+            Id(0) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 1485.,
+                        height: 1080.
+                    },
+                    position: Position { x: 0., y: 0. }
+                }
+            ),
+            Id(1) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 495.,
+                        height: 1080.
+                    },
+                    position: Position { x: 1485., y: 0. }
+                }
+            ),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    // The most recent window is taller, so it is split vertically
+    element_map
+        .push(Id(2), &mut |id, size_pos| match id {
+            Id(1) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 495.,
+                        height: 810.
+                    },
+                    position: Position { x: 1485., y: 0. }
+                }
+            ),
+            Id(2) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 495.,
+                        height: 270.
+                    },
+                    position: Position { x: 1485., y: 810. }
+                }
+            ),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+
+    // The splits follow the aspect ratio after remap
+    let portrait = SizeAndPos {
+        size: Size {
+            width: 1080.,
+            height: 1980.,
+        },
+        position: Position { x: 0., y: 0. },
+    };
+    element_map.remap(portrait, &mut |_, _| {});
+    assert!(matches!(element_map.root(), Element::Vertical { .. }));
+
+    // Spiral turns round
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_insert_policy(InsertPolicy::Dwindle(Dwindle {
+        spiral: true,
+        ..Default::default()
+    }));
+    for id in 0..4 {
+        element_map.push(Id(id), &mut |_, _| {}).expect("Should ok");
+    }
+    let position = |id| {
+        element_map
+            .find_window(Id(id))
+            .expect("Should exist")
+            .position()
+    };
+    assert_eq!(position(0), Position { x: 0., y: 0. });
+    assert_eq!(position(1), Position { x: 990., y: 0. });
+    assert_eq!(position(3), Position { x: 990., y: 540. });
+    assert_eq!(position(2), Position { x: 1485., y: 540. });
+}
+
+#[test]
+fn grid_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_layout(Some(Box::new(Grid::default())), &mut ());
+    for id in 0..4 {
+        element_map.push(Id(id), &mut |_, _| {}).expect("Should ok");
+    }
+    // ---------------------
+    // |   0   |   1   | 2 |
+    // |-------------------|
+    // |    3    |    4    |
+    // ---------------------
+    let mut times = 0;
+    element_map
+        .push(Id(4), &mut |id, size_pos: SizeAndPos| {
+            times += 1;
+            match id {
+                Id(0) | Id(1) | Id(2) => {
+                    assert_eq!(size_pos.size.width, 660.);
+                    assert_eq!(size_pos.size.height, 540.);
+                }
+                Id(3) => assert_eq!(size_pos.position, Position { x: 0., y: 540. }),
+                Id(4) => assert_eq!(
+                    size_pos,
+                    SizeAndPos {
+                        size: Size {
+                            width: 990.,
+                            height: 540.
+                        },
+                        position: Position { x: 990., y: 540. }
+                    }
+                ),
+                _ => unreachable!(),
+            }
+        })
+        .expect("Should ok");
+    assert_eq!(times, 5);
+
+    // The resize is kept after swap
+    element_map
+        .drag_resize(-270., Direction::Right, Id(3), &mut |_, _| {})
+        .expect("Should ok");
+    element_map
+        .swap(Id(3), Id(4), &mut |id, size_pos: SizeAndPos| match id {
+            Id(3) => assert_eq!(size_pos.size.width, 1260.),
+            Id(4) => assert_eq!(size_pos.size.width, 720.),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    assert_eq!(
+        element_map.layout_windows(),
+        Some(&[Id(0), Id(1), Id(2), Id(4), Id(3)][..])
+    );
+
+    // And the grid is rebuilt when a window is gone
+    element_map
+        .delete(Id(0), &mut |id, size_pos: SizeAndPos| match id {
+            Id(1) | Id(2) | Id(4) | Id(3) => assert_eq!(size_pos.size.width, 990.),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+}
+
+#[test]
+fn scrolling_test() {
+    let mut scrolling = Scrolling::new(DISPLAY_SIZE, vec![0.25, 0.5, 0.75]);
+    scrolling
+        .insert_column(Id(0), &mut |_, _| {})
+        .expect("Should ok");
+    scrolling
+        .insert_column(Id(1), &mut |_, _| {})
+        .expect("Should ok");
+    //      --------------------
+    // | 0  |   1    |    2    |
+    //      --------------------
+    let mut times = 0;
+    scrolling
+        .insert_column(Id(2), &mut |id, size_pos: SizeAndPos| {
+            times += 1;
+            match id {
+                Id(0) => assert_eq!(size_pos.position.x, -990.),
+                Id(1) => assert_eq!(size_pos.position.x, 0.),
+                Id(2) => assert_eq!(size_pos.position.x, 990.),
+                _ => unreachable!(),
+            }
+        })
+        .expect("Should ok");
+    assert_eq!(times, 3);
+    assert_eq!(scrolling.offset(), 990.);
+    assert!(!scrolling.is_visible(Id(0)));
+    assert!(scrolling.is_visible(Id(2)));
+
+    // Scroll back to the first column
+    scrolling
+        .focus_direction(Direction::Left, &mut |_, _| {})
+        .expect("Should ok");
+    scrolling
+        .focus_direction(Direction::Left, &mut |_, _| {})
+        .expect("Should ok");
+    assert_eq!(scrolling.focused(), Some(Id(0)));
+    assert_eq!(scrolling.offset(), 0.);
+    assert!(!scrolling.is_visible(Id(2)));
+
+    // Stack a window under 0
+    scrolling
+        .insert_in_column(Id(3), Id(0), &mut |id, size_pos| match id {
+            Id(0) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 990.,
+                        height: 540.
+                    },
+                    position: Position { x: 0., y: 0. }
+                }
+            ),
+            Id(3) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 990.,
+                        height: 540.
+                    },
+                    position: Position { x: 0., y: 540. }
+                }
+            ),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    scrolling
+        .cycle_width(Id(3), &mut |id, size_pos: SizeAndPos| match id {
+            Id(0) | Id(3) => assert_eq!(size_pos.size.width, 1485.),
+            Id(1) => assert_eq!(size_pos.position.x, 1485.),
+            Id(2) => assert_eq!(size_pos.position.x, 2475.),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    scrolling.delete(Id(3), &mut |_, _| {}).expect("Should ok");
+    assert_eq!(scrolling.focused(), Some(Id(0)));
+    assert!(scrolling.delete(Id(3), &mut ()).is_err());
+
+    // It is the layout of a map too, the view follows the new window
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_history(Some(10));
+    element_map.set_layout(
+        Some(Box::new(Scrolling::new(DISPLAY_SIZE, vec![0.5]))),
+        &mut (),
+    );
+    for id in 0..3 {
+        element_map.push(Id(id), &mut ()).expect("Should ok");
+    }
+    let x = |element_map: &TopElementMap, id| {
+        element_map
+            .placements()
+            .into_iter()
+            .find(|placement| placement.id == Id(id))
+            .map(|placement| (placement.size_pos.position.x, placement.visible))
+            .expect("Should exist")
+    };
+    assert_eq!(x(&element_map, 0), (-990., false));
+    assert_eq!(x(&element_map, 2), (990., true));
+    element_map
+        .swap(Id(1), Id(2), &mut |id, size_pos: SizeAndPos| match id {
+            Id(1) => assert_eq!(size_pos.position.x, 990.),
+            Id(2) => assert_eq!(size_pos.position.x, 0.),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    // The new column is put before the target column
+    element_map
+        .insert(Id(3), Id(0), Direction::Left, &mut ())
+        .expect("Should ok");
+    assert_eq!(
+        element_map.layout_windows(),
+        Some(&[Id(3), Id(0), Id(2), Id(1)][..])
+    );
+    assert_eq!(x(&element_map, 3), (0., true));
+    element_map.delete(Id(3), &mut ()).expect("Should ok");
+    assert!(element_map.undo(&mut ()));
+    assert_eq!(x(&element_map, 3), (0., true));
+}
+
+#[test]
+fn centered_master_test() {
+    let ultrawide = SizeAndPos {
+        size: Size {
+            width: 5120.,
+            height: 1440.,
+        },
+        position: Position { x: 0., y: 0. },
+    };
+    let centered_master = CenteredMaster::default();
+    assert!(centered_master.arrange(&[], ultrawide).is_empty());
+
+    // ---------------------------
+    // |      |    0     |       |
+    // ---------------------------
+    let placements = centered_master.arrange(&[Id(0)], ultrawide);
+    assert_eq!(
+        placements,
+        vec![Placement {
+            id: Id(0),
+            size_pos: SizeAndPos {
+                size: Size {
+                    width: 2560.,
+                    height: 1440.
+                },
+                position: Position { x: 1280., y: 0. }
+            },
+            visible: true,
+        }]
+    );
+
+    // ---------------------------
+    // |  1   |          |   2   |
+    // |------|    0     |       |
+    // |  3   |          |       |
+    // ---------------------------
+    let placements = centered_master.arrange(&[Id(0), Id(1), Id(2), Id(3)], ultrawide);
+    let size_pos = |id| {
+        placements
+            .iter()
+            .find(|placement| placement.id == Id(id))
+            .expect("Should exist")
+            .size_pos
+    };
+    assert_eq!(size_pos(1).position, Position { x: 0., y: 0. });
+    assert_eq!(size_pos(3).position, Position { x: 0., y: 720. });
+    assert_eq!(
+        size_pos(2),
+        SizeAndPos {
+            size: Size {
+                width: 1280.,
+                height: 1440.
+            },
+            position: Position { x: 3840., y: 0. }
+        }
+    );
+
+    // The empty stacks are skipped, so the integer units do not divide by zero
+    let size_pos = SizeAndPos {
+        size: Size {
+            width: 4000,
+            height: 1000,
+        },
+        position: Position { x: 0, y: 0 },
+    };
+    let mut element_map = TopElementMap::<i32>::new(size_pos);
+    element_map.push(Id(0), &mut ()).expect("Should ok");
+    element_map.set_layout(Some(Box::new(CenteredMaster::default())), &mut ());
+    assert_eq!(
+        element_map.placements()[0].size_pos.position,
+        Position { x: 1000, y: 0 }
+    );
+    // The map keeps its size when the lonely window is placed in the center
+    element_map.push(Id(1), &mut ()).expect("Should ok");
+    assert_eq!(
+        element_map.placements(),
+        centered_master.arrange(&[Id(0), Id(1)], size_pos)
+    );
+    let placements = centered_master.arrange(&[Id(0), Id(1)], size_pos);
+    assert_eq!(
+        placements[1].size_pos,
+        SizeAndPos {
+            size: Size {
+                width: 1000,
+                height: 1000
+            },
+            position: Position { x: 0, y: 0 }
+        }
+    );
+}
+
+#[test]
+fn autotiling_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_autotiling(Some(Autotiling::default()));
+    element_map.push(Id(0), &mut ()).expect("Should ok");
+    element_map.push(Id(1), &mut ()).expect("Should ok");
+    // --------------------
+    // |        |    1    |
+    // |   0    |---------|
+    // |        |    2    |
+    // --------------------
+    element_map
+        .push(Id(2), &mut |id, size_pos: SizeAndPos| match id {
+            Id(1) => assert_eq!(size_pos.position, Position { x: 990., y: 0. }),
+            Id(2) => assert_eq!(size_pos.position, Position { x: 990., y: 540. }),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    // The title bar makes the window 0 wider than it is high
+    let decorated = Autotiling {
+        title: 100.,
+        ..Default::default()
+    };
+    let window = element_map.find_window(Id(0)).expect("Should exist");
+    assert_eq!(
+        Autotiling::default().way(window.size_pos()),
+        InsertWay::Vertical
+    );
+    assert_eq!(decorated.way(window.size_pos()), InsertWay::Horizontal);
+    element_map.set_autotiling(Some(decorated));
+    element_map
+        .insert_auto(Id(3), Id(0), &mut |id, size_pos: SizeAndPos| match id {
+            Id(0) => assert_eq!(size_pos.size.width, 495.),
+            Id(3) => assert_eq!(size_pos.position, Position { x: 495., y: 0. }),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    assert!(matches!(
+        element_map.insert_auto(Id(4), Id(9), &mut ()),
+        Err(Error::ElementNotFound)
+    ));
+}
+
+#[test]
+fn switch_layout_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    element_map
+        .drag_resize(495., Direction::Right, Id(0), &mut |_, _| {})
+        .expect("Should ok");
+    element_map.set_layout(
+        Some(Box::new(MasterStack::default())),
+        &mut |id, size_pos: SizeAndPos| match id {
+            Id(0) | Id(1) => assert_eq!(size_pos.size.width, 990.),
+            _ => unreachable!(),
+        },
+    );
+    element_map.push(Id(2), &mut ()).expect("Should ok");
+    // ----------------------------
+    // |                |  1 | 2 |
+    // |       0        |    |   |
+    // |                |    |   |
+    // ----------------------------
+    let mut times = 0;
+    element_map.set_layout(None, &mut |id, size_pos: SizeAndPos| {
+        times += 1;
+        match id {
+            Id(0) => assert_eq!(size_pos.size.width, 1485.),
+            Id(1) => assert_eq!(
+                size_pos,
+                SizeAndPos {
+                    size: Size {
+                        width: 247.5,
+                        height: 1080.
+                    },
+                    position: Position { x: 1485., y: 0. }
+                }
+            ),
+            Id(2) => assert_eq!(size_pos.position, Position { x: 1732.5, y: 0. }),
+            _ => unreachable!(),
+        }
+    });
+    assert_eq!(times, 3);
+    assert!(element_map.layout().is_none());
+
+    // The window deleted in the automatic layout is removed from the tree
+    element_map.set_layout(Some(Box::new(Grid::default())), &mut ());
+    element_map.delete(Id(0), &mut ()).expect("Should ok");
+    element_map.set_layout(None, &mut ());
+    assert!(element_map.find_window(Id(0)).is_none());
+    assert_eq!(element_map.root().window_count(), 2);
+    assert_eq!(
+        element_map
+            .find_window(Id(1))
+            .expect("Should exist")
+            .height(),
+        1080.
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(2), Id(1), InsertWay::Vertical, &mut |_, _| {});
+    element_map
+        .drag_resize(495., Direction::Right, Id(0), &mut |_, _| {})
+        .expect("Should ok");
+    element_map.focus(Id(2)).expect("Should ok");
+    let saved = serde_json::to_string(&element_map).expect("Should ok");
+    let loaded: TopElementMap = serde_json::from_str(&saved).expect("Should ok");
+    assert_eq!(loaded.root().windows(), element_map.root().windows());
+    assert_eq!(loaded.focused(), Some(Id(2)));
+
+    // The window 2 is saved as the window 1
+    let duplicated = saved.replace("\"Window\":{\"id\":2", "\"Window\":{\"id\":1");
+    assert_ne!(duplicated, saved);
+    assert!(serde_json::from_str::<TopElementMap>(&duplicated).is_err());
+    let newer = saved.replace(
+        &format!("\"version\":{FORMAT_VERSION}"),
+        &format!("\"version\":{}", FORMAT_VERSION + 1),
+    );
+    assert!(serde_json::from_str::<TopElementMap>(&newer).is_err());
+    let element = serde_json::to_string(element_map.find_window(Id(0)).expect("Should exist"))
+        .expect("Should ok");
+    let broken = element.replace("\"width\":0.75", "\"width\":1.5");
+    assert_ne!(broken, element);
+    let broken: Element = serde_json::from_str(&broken).expect("Should ok");
+    assert!(matches!(broken.validate(), Err(Error::InvalidLayout(_))));
+
+    // The version is read first, a newer format is told even when its fields are not known
+    let future = format!("{{\"version\":{},\"screens\":[]}}", FORMAT_VERSION + 1);
+    let error = serde_json::from_str::<TopElementMap>(&future).expect_err("Should fail");
+    assert!(
+        error
+            .to_string()
+            .contains(&Error::UnsupportedVersion(FORMAT_VERSION + 1).to_string())
+    );
+    // The window 0 is moved over the window 1, it is still in the map
+    let moved = saved.replacen("\"x\":0.0", "\"x\":100.0", 1);
+    assert_ne!(moved, saved);
+    let error = serde_json::from_str::<TopElementMap>(&moved).expect_err("Should fail");
+    assert!(error.to_string().contains("percent puts it"));
+    // The placeholders are saved with the time left
+    element_map
+        .add_placeholder(
+            Id(1),
+            Criteria {
+                app_id: Some("foot".to_string()),
+                ..Default::default()
+            },
+            Some(Duration::from_secs(60)),
+        )
+        .expect("Should ok");
+    let saved = serde_json::to_string(&element_map).expect("Should ok");
+    let loaded: TopElementMap = serde_json::from_str(&saved).expect("Should ok");
+    assert_eq!(loaded.placeholders().len(), 1);
+    assert_eq!(loaded.placeholders()[0].slot, Id(1));
+    let expires = loaded.placeholders()[0].expires.expect("Should exist");
+    assert!(expires > Instant::now() + Duration::from_secs(50));
+    // The maps saved before the placeholders are still read
+    let old = saved
+        .replace(&format!("\"version\":{FORMAT_VERSION}"), "\"version\":1")
+        .replace(
+            &saved[saved.find(",\"placeholders\"").expect("Should exist")..saved.len() - 1],
+            "",
+        );
+    let loaded: TopElementMap = serde_json::from_str(&old).expect("Should ok");
+    assert!(loaded.placeholders().is_empty());
+    // The new ids are not the ones of the loaded windows
+    let far = old.replace("\"Window\":{\"id\":2", "\"Window\":{\"id\":5000");
+    let far = far.replace("\"focused\":2", "\"focused\":5000");
+    let loaded: TopElementMap = serde_json::from_str(&far).expect("Should ok");
+    assert!(loaded.find_window(Id(5000)).is_some());
+    assert!(Id::unique().0 > 5000);
+    // The tree of a layout which places the windows is not saved
+    element_map.set_layout(Some(Box::new(CenteredMaster::default())), &mut ());
+    assert!(serde_json::to_string(&element_map).is_err());
+}
+
+#[test]
+fn placeholder_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(100), Id(100), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(101), Id(100), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(102), Id(101), InsertWay::Vertical, &mut |_, _| {});
+    element_map
+        .drag_resize(495., Direction::Right, Id(100), &mut |_, _| {})
+        .expect("Should ok");
+    let terminal = Criteria {
+        app_id: Some("foot".to_string()),
+        ..Default::default()
+    };
+    for slot in [100, 101] {
+        element_map
+            .add_placeholder(Id(slot), terminal.clone(), None)
+            .expect("Should ok");
+    }
+    let editor = Criteria {
+        title: Some("* - Vim".to_string()),
+        ..Default::default()
+    };
+    element_map
+        .add_placeholder(Id(102), editor, Some(Duration::from_secs(10)))
+        .expect("Should ok");
+    assert!(matches!(
+        element_map.add_placeholder(Id(9), Criteria::default(), None),
+        Err(Error::ElementNotFound)
+    ));
+    // The slots are never focused
+    assert!(matches!(
+        element_map.focus(Id(100)),
+        Err(Error::ElementNotFound)
+    ));
+    assert_eq!(element_map.focus_next(), None);
+    // The slots are never dispatched, in the monocle mode too
+    element_map.remap(DISPLAY_SIZE, &mut |_, _| unreachable!());
+    element_map.set_monocle(true, &mut |_, _| unreachable!());
+    element_map.remap(DISPLAY_SIZE, &mut |_, _| unreachable!());
+    element_map.set_monocle(false, &mut |_, _| unreachable!());
+
+    let browser = WindowProps {
+        app_id: Some("firefox"),
+        title: Some("Firefox"),
+        class: None,
+    };
+    assert!(
+        !element_map
+            .swallow(Id(0), &browser, &mut |_, _| unreachable!())
+            .expect("Should ok")
+    );
+    // ----------------------------
+    // |                |   101  |
+    // |       0        |--------|
+    // |                |   102  |
+    // ----------------------------
+    let foot = WindowProps {
+        app_id: Some("foot"),
+        ..Default::default()
+    };
+    let mut times = 0;
+    assert!(
+        element_map
+            .swallow(Id(0), &foot, &mut |id, size_pos: SizeAndPos| {
+                times += 1;
+                assert_eq!(id, Id(0));
+                assert_eq!(size_pos.size.width, 1485.);
+            })
+            .expect("Should ok")
+    );
+    assert_eq!(times, 1);
+    assert_eq!(element_map.focus_next(), Some(Id(0)));
+    assert_eq!(element_map.focus_next(), Some(Id(0)));
+    assert!(matches!(
+        element_map.swallow(Id(0), &foot, &mut ()),
+        Err(Error::DuplicateId)
+    ));
+    let vim = WindowProps {
+        title: Some("main.rs - Vim"),
+        ..Default::default()
+    };
+    assert!(
+        element_map
+            .swallow(Id(1), &vim, &mut ())
+            .expect("Should ok")
+    );
+    assert_eq!(
+        element_map
+            .find_window(Id(1))
+            .expect("Should exist")
+            .position(),
+        Position { x: 1485., y: 540. }
+    );
+    assert_eq!(element_map.placeholders().len(), 1);
+
+    // The slot 101 is never swallowed, the window 1 takes its space when it is removed
+    element_map.expire_placeholders(Instant::now(), &mut ());
+    assert_eq!(element_map.placeholders().len(), 1);
+    element_map.clear_placeholders(&mut |id, size_pos: SizeAndPos| {
+        assert_eq!(id, Id(1));
+        assert_eq!(size_pos.size.height, 1080.);
+    });
+    assert!(element_map.placeholders().is_empty());
+    assert!(element_map.find_window(Id(101)).is_none());
+
+    // The layout which places the windows knows the new id
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_layout(Some(Box::new(CenteredMaster::default())), &mut ());
+    for id in 0..3 {
+        element_map.push(Id(id), &mut ()).expect("Should ok");
+    }
+    element_map
+        .add_placeholder(Id(2), terminal, None)
+        .expect("Should ok");
+    assert!(
+        element_map
+            .swallow(Id(9), &foot, &mut ())
+            .expect("Should ok")
+    );
+    let ids: Vec<Id> = element_map
+        .placements()
+        .iter()
+        .map(|placement| placement.id)
+        .collect();
+    assert_eq!(ids, vec![Id(0), Id(1), Id(9)]);
+}
+
+#[cfg(feature = "i3")]
+#[test]
+fn i3_import_test() {
+    use crate::i3::ImportWarning;
+    let layout = r#"
+// vim:ts=4:sw=4:et
+{
+    // splith split container with 2 children
+    "layout": "splith",
+    "percent": 1,
+    "type": "con",
+    "nodes": [
+        {
+            "border": "pixel",
+            "current_border_width": 2,
+            "marks": ["editor"],
+            "name": "vim",
+            "percent": 0.75,
+            "swallows": [
+               {
+               "class": "^URxvt$",
+               "instance": "^vim$"
+               }
+            ],
+            "type": "con"
+        },
+        {
+            "fullscreen_mode": 1,
+            "layout": "tabbed",
+            "percent": 0.25,
+            "type": "con",
+            "nodes": [
+                { "swallows": [ { "title": "^Mozilla .*$" } ], "type": "con" },
+                { "swallows": [ { "class": "^Firefox$" } ], "type": "con" }
+            ]
+        }
+    ]
+}
+"#;
+    let mut next = 0;
+    let (mut element_map, warnings) = TopElementMap::from_i3_layout(layout, DISPLAY_SIZE, || {
+        next += 1;
+        Id(100 + next)
+    })
+    .expect("Should ok");
+    assert_eq!(
+        warnings,
+        vec![
+            ImportWarning::Name("vim".to_string()),
+            ImportWarning::Marks(vec!["editor".to_string()]),
+            ImportWarning::Border {
+                style: Some("pixel".to_string()),
+                width: Some(2)
+            },
+            ImportWarning::UnsupportedCriterion {
+                key: "instance".to_string(),
+                value: "^vim$".to_string()
+            },
+            ImportWarning::Fullscreen,
+            ImportWarning::UnsupportedLayout("tabbed".to_string()),
+        ]
+    );
+    // ----------------------------
+    // |                |102 |103|
+    // |      101       |    |   |
+    // |                |    |   |
+    // ----------------------------
+    let window = |id| element_map.find_window(Id(id)).expect("Should exist");
+    assert_eq!(window(101).width(), 1485.);
+    assert_eq!(window(103).position(), Position { x: 1732.5, y: 0. });
+    assert_eq!(
+        element_map.placeholders()[1].criteria.title.as_deref(),
+        Some("Mozilla *")
+    );
+    let firefox = WindowProps {
+        class: Some("Firefox"),
+        ..Default::default()
+    };
+    element_map
+        .swallow(Id(0), &firefox, &mut |id, size_pos: SizeAndPos| {
+            assert_eq!(id, Id(0));
+            assert_eq!(size_pos.position, Position { x: 1732.5, y: 0. });
+        })
+        .expect("Should ok");
+    assert!(
+        TopElementMap::<f32>::from_i3_layout("{ \"nodes\": [", DISPLAY_SIZE, || Id(0)).is_err()
+    );
+}
+
+#[cfg(feature = "i3")]
+#[test]
+fn i3_tree_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(2), Id(1), InsertWay::Vertical, &mut |_, _| {});
+    element_map.focus(Id(2)).expect("Should ok");
+    let tree = element_map.to_i3_tree("HDMI-A-1", "1");
+    assert_eq!(tree["type"], "root");
+    let output = &tree["nodes"][0];
+    assert_eq!(output["name"], "HDMI-A-1");
+    let workspace = &output["nodes"][0];
+    assert_eq!(workspace["type"], "workspace");
+    assert_eq!(workspace["layout"], "splith");
+    assert_eq!(workspace["rect"]["width"], 1980);
+    let stack = &workspace["nodes"][1];
+    assert_eq!(stack["layout"], "splitv");
+    assert_eq!(stack["percent"], 0.5);
+    assert_eq!(workspace["focus"][0], stack["id"]);
+    let window = &stack["nodes"][1];
+    assert_eq!(window["id"], 2);
+    assert_eq!(window["window"], 2);
+    assert_eq!(window["focused"], true);
+    assert_eq!(
+        window["rect"],
+        serde_json::json!({ "x": 990, "y": 540, "width": 990, "height": 540 })
+    );
+    // The containers do not take the ids of the windows
+    assert!(stack["id"].as_u64().expect("Should exist") > 2);
+
+    // A placeholder is written like a placeholder of i3 which is not swallowed yet
+    let criteria = Criteria {
+        app_id: Some("foot".to_string()),
+        title: Some("vim *.rs".to_string()),
+        class: None,
+    };
+    element_map
+        .add_placeholder(Id(1), criteria, None)
+        .expect("Should ok");
+    let tree = element_map.to_i3_tree("HDMI-A-1", "1");
+    let slot = &tree["nodes"][0]["nodes"][0]["nodes"][1]["nodes"][0];
+    assert_eq!(slot["id"], 1);
+    assert_eq!(slot["window"], serde_json::Value::Null);
+    assert_eq!(
+        slot["swallows"],
+        serde_json::json!([{ "app_id": "^foot$", "title": "^vim .*\\.rs$" }])
+    );
+    assert_eq!(window["swallows"], serde_json::json!([]));
+}
+
+#[test]
+fn layout_str_test() {
+    // --------------------
+    // |     |     2      |
+    // |  1  |------------|
+    // |     |     3      |
+    // --------------------
+    let element_map =
+        TopElementMap::from_layout_str("H[1:0.25, V[2, 3]]", DISPLAY_SIZE).expect("Should ok");
+    assert_eq!(
+        element_map
+            .find_window(Id(1))
+            .expect("Should exist")
+            .width(),
+        495.
+    );
+    assert_eq!(
+        element_map
+            .find_window(Id(3))
+            .expect("Should exist")
+            .size_pos(),
+        SizeAndPos {
+            size: Size {
+                width: 1485.,
+                height: 540.
+            },
+            position: Position { x: 495., y: 540. }
+        }
+    );
+    assert_eq!(element_map.to_string(), "H[1:0.25, V[2, 3]:0.75]");
+
+    // The tree made by the calls is printed, and read back to the same tree
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(2), Id(1), InsertWay::Vertical, &mut |_, _| {});
+    element_map
+        .drag_resize(100., Direction::Right, Id(0), &mut |_, _| {})
+        .expect("Should ok");
+    element_map
+        .wrap(Id(0), InsertWay::Vertical)
+        .expect("Should ok");
+    let text = element_map.to_string();
+    let loaded = TopElementMap::from_layout_str(&text, DISPLAY_SIZE).expect("Should ok");
+    assert_eq!(loaded.to_string(), text);
+    assert_eq!(loaded.root().windows(), element_map.root().windows());
+    assert!(text.starts_with("H[V![0]:"));
+    assert_eq!(
+        TopElementMap::from_layout_str("", DISPLAY_SIZE)
+            .expect("Should ok")
+            .to_string(),
+        ""
+    );
+
+    let column = |text| match TopElementMap::from_layout_str(text, DISPLAY_SIZE) {
+        Err(Error::ParseLayout { column, .. }) => column,
+        _ => unreachable!(),
+    };
+    assert_eq!(column("H[1, V[2 3]]"), 10);
+    assert_eq!(column("H[1, 1]"), 6);
+    assert_eq!(column("H[1:0.5, 2:0.6]"), 2);
+    assert_eq!(column("H[1:2, 2]"), 5);
+    assert_eq!(column("H[1:1, 2]"), 2);
+    assert_eq!(column("H[1, 2] 3"), 9);
+
+    // The new ids are not the ones of the windows in the text
+    TopElementMap::<f32>::from_layout_str("H[6000, 6001]", DISPLAY_SIZE).expect("Should ok");
+    assert!(Id::unique().0 > 6001);
+}
+
+#[test]
+fn render_test() {
+    let mut element_map =
+        TopElementMap::from_layout_str("H[1:0.25, V[2, 3]]", DISPLAY_SIZE).expect("Should ok");
+    assert_eq!(
+        element_map.render(21, 7, RenderStyle::Ascii),
+        [
+            "---------------------",
+            "|    |      2       |",
+            "|    |              |",
+            "| 1  |--------------|",
+            "|    |      3       |",
+            "|    |              |",
+            "---------------------",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        element_map.render(21, 7, RenderStyle::Unicode),
+        [
+            "┌────┬──────────────┐",
+            "│    │      2       │",
+            "│    │              │",
+            "│ 1  ├──────────────┤",
+            "│    │      3       │",
+            "│    │              │",
+            "└────┴──────────────┘",
+        ]
+        .join("\n")
+    );
+    // The id does not fit in the small boxes
+    let small = TopElementMap::from_layout_str("H[10, 20, 30]", DISPLAY_SIZE)
+        .expect("Should ok")
+        .render(7, 3, RenderStyle::Ascii);
+    assert_eq!(small, ["-------", "|#|#|#|", "-------"].join("\n"));
+    // Only the focused window is seen in the monocle mode
+    element_map.focus(Id(3)).expect("Should ok");
+    element_map.set_monocle(true, &mut ());
+    assert_eq!(
+        element_map.render(7, 3, RenderStyle::Ascii),
+        ["-------", "|  3  |", "-------"].join("\n")
+    );
+}
+
+#[test]
+fn svg_test() {
+    let mut element_map =
+        TopElementMap::from_layout_str("H[1:0.25, V[2, 3]]", DISPLAY_SIZE).expect("Should ok");
+    element_map.focus(Id(2)).expect("Should ok");
+    let svg = element_map.to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 1980 1080\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("class=\"window").count(), 3);
+    assert!(svg.contains(
+        "<rect class=\"window focused\" x=\"495\" y=\"0\" width=\"1485\" height=\"540\""
+    ));
+    assert!(svg.contains(">Id 1</text>"));
+    assert!(svg.contains(">25%</text>"));
+    assert_eq!(svg.matches(">50%</text>").count(), 2);
+    assert_eq!(svg.matches("class=\"container\"").count(), 2);
+    assert!(svg.contains(">H →</text>") && svg.contains(">V ↓</text>"));
+    assert!(!svg.contains("class=\"gap\""));
+
+    element_map.set_autotiling(Some(Autotiling {
+        gaps: Size {
+            width: 10.,
+            height: 10.,
+        },
+        ..Default::default()
+    }));
+    let svg = element_map.to_svg();
+    assert!(svg.contains("<rect class=\"gap\" x=\"10\" y=\"10\" width=\"475\" height=\"1060\""));
+}
+
+#[test]
+fn history_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_history(Some(3));
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    element_map
+        .drag_resize(495., Direction::Right, Id(0), &mut |_, _| {})
+        .expect("Should ok");
+    // A failed operation is not a step
+    assert!(element_map.swap(Id(0), Id(9), &mut ()).is_err());
+    assert_eq!(element_map.to_string(), "H[0:0.75, 1:0.25]");
+
+    let mut times = 0;
+    assert!(element_map.undo(&mut |id, size_pos: SizeAndPos| {
+        times += 1;
+        match id {
+            Id(0) => assert_eq!(size_pos.size.width, 990.),
+            Id(1) => assert_eq!(size_pos.position, Position { x: 990., y: 0. }),
+            _ => unreachable!(),
+        }
+    }));
+    assert_eq!(times, 2);
+    assert!(element_map.undo(&mut |id, size_pos: SizeAndPos| {
+        assert_eq!(id, Id(0));
+        assert_eq!(size_pos, DISPLAY_SIZE);
+    }));
+    assert!(element_map.find_window(Id(1)).is_none());
+    assert!(element_map.redo(&mut ()));
+    assert!(element_map.redo(&mut ()));
+    assert!(!element_map.redo(&mut ()));
+    assert_eq!(element_map.to_string(), "H[0:0.75, 1:0.25]");
+
+    // The group is one step
+    element_map.begin_group();
+    element_map.swap(Id(0), Id(1), &mut ()).expect("Should ok");
+    element_map.push(Id(2), &mut |_, _| {}).expect("Should ok");
+    element_map.end_group();
+    assert_eq!(element_map.to_string(), "H[0:0.25, 1:0.375, 2:0.375]");
+    assert!(element_map.undo(&mut ()));
+    assert_eq!(element_map.to_string(), "H[0:0.75, 1:0.25]");
+
+    // Only 3 steps are kept
+    assert!(element_map.undo(&mut ()));
+    assert!(element_map.undo(&mut ()));
+    assert!(!element_map.can_undo());
+    assert_eq!(element_map.to_string(), "0");
+    element_map.set_history(None);
+    assert!(!element_map.undo(&mut ()));
+
+    // Switching the layout is one step, the windows merged back are not steps
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_history(Some(10));
+    element_map.push(Id(1), &mut ()).expect("Should ok");
+    element_map.push(Id(2), &mut ()).expect("Should ok");
+    element_map.set_layout(Some(Box::new(MasterStack::default())), &mut ());
+    element_map.push(Id(3), &mut ()).expect("Should ok");
+    element_map.push(Id(4), &mut ()).expect("Should ok");
+    element_map.delete(Id(1), &mut ()).expect("Should ok");
+    element_map.set_layout(None, &mut ());
+    assert!(element_map.layout().is_none());
+    assert!(element_map.undo(&mut ()));
+    assert_eq!(
+        element_map.layout_windows(),
+        Some(&[Id(2), Id(3), Id(4)][..])
+    );
+    assert!(element_map.undo(&mut ()));
+    assert!(element_map.find_window(Id(1)).is_some());
+    assert!(element_map.undo(&mut ()));
+    assert!(element_map.undo(&mut ()));
+    assert!(element_map.undo(&mut ()));
+    assert!(element_map.layout().is_none());
+    assert_eq!(element_map.to_string(), "H[1, 2]");
+}
+
+#[test]
+fn transaction_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+
+    // Every changed window is dispatched once, with where it is at the end
+    let mut ids = Vec::new();
+    element_map
+        .transaction(
+            &mut |id, size_pos: SizeAndPos| {
+                ids.push(id);
+                if id == Id(2) {
+                    assert_eq!(size_pos.size.width, 495.);
+                }
+            },
+            |map| {
+                map.push(Id(2), &mut ())?;
+                map.swap(Id(0), Id(1), &mut ())
+            },
+        )
+        .expect("Should ok");
+    ids.sort_by_key(|id| id.0);
+    assert_eq!(ids, vec![Id(0), Id(1), Id(2)]);
+    assert_eq!(element_map.to_string(), "H[0:0.25, 1:0.5, 2:0.25]");
+
+    // A failed step brings the map back, and nothing is dispatched
+    let result = element_map.transaction(&mut |_, _| unreachable!(), |map| {
+        map.delete(Id(2), &mut ())?;
+        map.swap(Id(0), Id(9), &mut ())
+    });
+    assert!(matches!(result, Err(Error::ElementNotFound)));
+    assert_eq!(element_map.to_string(), "H[0:0.25, 1:0.5, 2:0.25]");
+    // The settings changed by the failed step are brought back too
+    let result = element_map.transaction(&mut |_, _| unreachable!(), |map| {
+        map.set_auto_normalize(true);
+        map.swap(Id(0), Id(9), &mut ())
+    });
+    assert!(result.is_err());
+    assert!(!element_map.auto_normalize);
+
+    // The window is not lost when it cannot be dropped
+    assert!(
+        element_map
+            .drag_and_drop(Id(0), Id(9), Direction::Left, &mut |_, _| unreachable!())
+            .is_err()
+    );
+    assert_eq!(element_map.to_string(), "H[0:0.25, 1:0.5, 2:0.25]");
+}
+
+#[test]
+fn layout_event_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.push(Id(0), &mut ()).expect("Should ok");
+
+    let mut events = Vec::new();
+    element_map
+        .observe(&mut |event| events.push(event), |map| {
+            map.push(Id(1), &mut ())?;
+            map.focus(Id(1))
+        })
+        .expect("Should ok");
+    let half = Size {
+        width: 990.,
+        height: 1080.,
+    };
+    assert_eq!(
+        events,
+        vec![
+            LayoutEvent::Resized {
+                id: Id(0),
+                old: DISPLAY_SIZE,
+                new: SizeAndPos {
+                    size: half,
+                    position: Position::zero()
+                }
+            },
+            LayoutEvent::Placed {
+                id: Id(1),
+                new: SizeAndPos {
+                    size: half,
+                    position: Position { x: 990., y: 0. }
+                },
+                visible: true
+            },
+            LayoutEvent::ContainerChanged {
+                path: vec![],
+                old: None,
+                new: Some((InsertWay::Horizontal, DISPLAY_SIZE))
+            },
+            LayoutEvent::FocusChanged {
+                old: None,
+                new: Some(Id(1))
+            },
+        ]
+    );
+
+    // The windows behind the focused one are hidden in the monocle mode
+    let mut events = Vec::new();
+    element_map
+        .observe(&mut |event| events.push(event), |map| {
+            map.set_monocle(true, &mut ());
+            Ok(())
+        })
+        .expect("Should ok");
+    assert!(matches!(events[0], LayoutEvent::Hidden { id: Id(0), .. }));
+    assert!(
+        matches!(events[1], LayoutEvent::Resized { id: Id(1), new, .. } if new == DISPLAY_SIZE)
+    );
+
+    // The old callback is still there as an adapter
+    let mut times = 0;
+    element_map
+        .observe(
+            &mut Dispatch(|id, size_pos: SizeAndPos| {
+                times += 1;
+                assert_eq!(id, Id(0));
+                assert_eq!(size_pos, DISPLAY_SIZE);
+            }),
+            |map| map.delete(Id(1), &mut ()),
+        )
+        .expect("Should ok");
+    assert_eq!(times, 1);
+
+    // A window put behind the focused one is placed hidden, it is dispatched once
+    let mut events = Vec::new();
+    element_map
+        .observe(&mut |event| events.push(event), |map| {
+            map.push(Id(2), &mut ())
+        })
+        .expect("Should ok");
+    assert!(matches!(
+        events[0],
+        LayoutEvent::Placed {
+            id: Id(2),
+            visible: false,
+            ..
+        }
+    ));
+    assert!(
+        !events
+            .iter()
+            .any(|event| matches!(event, LayoutEvent::Hidden { .. }))
+    );
+    let mut times = 0;
+    element_map
+        .observe(
+            &mut Dispatch(|id, _: SizeAndPos| {
+                assert_eq!(id, Id(3));
+                times += 1;
+            }),
+            |map| map.push(Id(3), &mut ()),
+        )
+        .expect("Should ok");
+    assert_eq!(times, 1);
+}
+
+#[test]
+fn preview_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+
+    let mut changes = element_map
+        .preview_insert(Id(2), Id(1), Direction::Bottom)
+        .expect("Should ok");
+    assert_eq!(element_map.to_string(), "H[0, 1]");
+    let mut dispatched = Vec::new();
+    element_map
+        .insert(Id(2), Id(1), Direction::Bottom, &mut |id, size_pos| {
+            dispatched.push((id, size_pos))
+        })
+        .expect("Should ok");
+    changes.sort_by_key(|(id, _)| id.0);
+    dispatched.sort_by_key(|(id, _)| id.0);
+    assert_eq!(changes, dispatched);
+
+    let changes = element_map
+        .preview_drag_resize(495., Direction::Right, Id(0))
+        .expect("Should ok");
+    assert!(
+        changes
+            .iter()
+            .any(|(id, size_pos)| *id == Id(0) && size_pos.size.width == 1485.)
+    );
+    assert_eq!(
+        element_map
+            .find_window(Id(0))
+            .expect("Should exist")
+            .size()
+            .width,
+        990.
+    );
+
+    // It fails like the real operation
+    assert!(matches!(
+        element_map.preview_drag_and_drop(Id(0), Id(9), Direction::Left),
+        Err(Error::ElementNotFound)
+    ));
+    let changes = element_map
+        .preview_drag_and_drop(Id(0), Id(2), Direction::Top)
+        .expect("Should ok");
+    assert!(changes.iter().any(|(id, _)| *id == Id(0)));
+    assert_eq!(element_map.to_string(), "H[0, V[1, 2]]");
+}
+
+/// Every window takes a row, the newest one is on the top
+#[derive(Debug, Clone)]
+struct Rows;
+
+impl LayoutPolicy<f32> for Rows {
+    fn arrange(&self, windows: &[Id], size_pos: SizeAndPos) -> Vec<Placement> {
+        let height = size_pos.size.height / windows.len() as f32;
+        windows
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(index, id)| Placement {
+                id: *id,
+                size_pos: SizeAndPos {
+                    size: Size {
+                        width: size_pos.size.width,
+                        height,
+                    },
+                    position: Position {
+                        x: size_pos.position.x,
+                        y: size_pos.position.y + height * index as f32,
+                    },
+                },
+                visible: true,
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn layout_policy_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_layout(Some(Box::new(Rows)), &mut ());
+    element_map.push(Id(0), &mut |_, _| {}).expect("Should ok");
+    // --------------------
+    // |        1         |
+    // |------------------|
+    // |        0         |
+    // --------------------
+    element_map
+        .push(Id(1), &mut |id, size_pos: SizeAndPos| match id {
+            Id(0) => assert_eq!(size_pos.position, Position { x: 0., y: 540. }),
+            Id(1) => assert_eq!(size_pos.position, Position { x: 0., y: 0. }),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    assert_eq!(
+        element_map
+            .find_window(Id(1))
+            .expect("Should exist")
+            .height(),
+        540.
+    );
+    element_map
+        .swap(Id(0), Id(1), &mut |id, size_pos: SizeAndPos| match id {
+            Id(0) => assert_eq!(size_pos.position, Position { x: 0., y: 0. }),
+            Id(1) => assert_eq!(size_pos.position, Position { x: 0., y: 540. }),
+            _ => unreachable!(),
+        })
+        .expect("Should ok");
+    // The policy does not take the drag and there is no tree to resize
+    assert!(matches!(
+        element_map.drag_resize(10., Direction::Bottom, Id(0), &mut ()),
+        Err(Error::DragIllegal)
+    ));
+    assert!(matches!(
+        element_map.set_master_ratio(0.75, &mut ()),
+        Err(Error::LayoutNotActive)
+    ));
+    element_map
+        .update_layout(|_: &mut Rows| {}, &mut ())
+        .expect("Should ok");
+    element_map.remap(
+        SizeAndPos {
+            size: Size {
+                width: 1000.,
+                height: 1000.,
+            },
+            position: Position { x: 0., y: 0. },
+        },
+        &mut |id, size_pos: SizeAndPos| match id {
+            Id(0) => assert_eq!(size_pos.position, Position { x: 0., y: 0. }),
+            Id(1) => assert_eq!(size_pos.position, Position { x: 0., y: 500. }),
+            _ => unreachable!(),
+        },
+    );
+    element_map.delete(Id(0), &mut ()).expect("Should ok");
+    assert_eq!(
+        element_map.placements(),
+        vec![Placement {
+            id: Id(1),
+            size_pos: SizeAndPos {
+                size: Size {
+                    width: 1000.,
+                    height: 1000.
+                },
+                position: Position { x: 0., y: 0. }
+            },
+            visible: true,
+        }]
+    );
+
+    // The master-stack takes the drag between the master and the stack
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_layout(Some(Box::new(MasterStack::default())), &mut ());
+    for id in 0..3 {
+        element_map.push(Id(id), &mut |_, _| {}).expect("Should ok");
+    }
+    element_map
+        .drag_resize(495., Direction::Right, Id(0), &mut ())
+        .expect("Should ok");
+    element_map.push(Id(3), &mut ()).expect("Should ok");
+    assert_eq!(
+        element_map
+            .find_window(Id(0))
+            .expect("Should exist")
+            .width(),
+        1485.
+    );
+    assert_eq!(
+        element_map
+            .find_window(Id(3))
+            .expect("Should exist")
+            .width(),
+        495.
+    );
+
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    element_map.set_layout(Some(Box::new(CenteredMaster::default())), &mut ());
+    element_map.push(Id(0), &mut ()).expect("Should ok");
+    assert_eq!(
+        element_map
+            .find_window(Id(0))
+            .expect("Should exist")
+            .position(),
+        Position { x: 495., y: 0. }
+    );
+    // The tree it keeps is not where the windows are, it cannot be edited
+    element_map.push(Id(1), &mut ()).expect("Should ok");
+    element_map.push(Id(2), &mut ()).expect("Should ok");
+    let placements = element_map.placements();
+    assert!(matches!(
+        element_map.balance(&mut |_, _| unreachable!()),
+        Err(Error::TreeNotEditable)
+    ));
+    assert!(matches!(
+        element_map.rotate(Rotation::Deg90, &mut |_, _| unreachable!()),
+        Err(Error::TreeNotEditable)
+    ));
+    assert!(matches!(
+        element_map.toggle_split(Id(1), &mut |_, _| unreachable!()),
+        Err(Error::TreeNotEditable)
+    ));
+    assert!(matches!(
+        element_map.wrap(Id(1), InsertWay::Vertical),
+        Err(Error::TreeNotEditable)
+    ));
+    assert!(matches!(
+        element_map.normalize(),
+        Err(Error::TreeNotEditable)
+    ));
+    assert_eq!(element_map.placements(), placements);
+}
+
+#[test]
+fn monocle_test() {
+    let mut element_map = TopElementMap::new(DISPLAY_SIZE);
+    let _ = element_map.insert_new(Id(0), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(1), Id(0), InsertWay::Horizontal, &mut |_, _| {});
+    let _ = element_map.insert_new(Id(2), Id(1), InsertWay::Vertical, &mut |_, _| {});
+    let _ = element_map.drag_resize(-270., Direction::Bottom, Id(1), &mut |_, _| {});
+    let tiling = element_map.root().windows();
+
+    let mut times = 0;
+    element_map.set_monocle(true, &mut |_, size_pos| {
+        times += 1;
+        assert_eq!(size_pos, DISPLAY_SIZE);
+    });
+    assert_eq!(times, 3);
+    assert_eq!(element_map.focused(), Some(Id(0)));
+    let visible: Vec<Id> = element_map
+        .placements()
+        .into_iter()
+        .filter(|placement| placement.visible)
+        .map(|placement| placement.id)
+        .collect();
+    assert_eq!(visible, vec![Id(0)]);
+
+    // Only the new window is dispatched
+    element_map
+        .insert_new(Id(3), Id(2), InsertWay::Horizontal, &mut |id, size_pos| {
+            assert_eq!(id, Id(3));
+            assert_eq!(size_pos, DISPLAY_SIZE);
+        })
+        .expect("Should ok");
+    assert_eq!(element_map.focus_prev(), Some(Id(3)));
+    assert_eq!(element_map.focus_next(), Some(Id(0)));
+    assert_eq!(element_map.focus_next(), Some(Id(1)));
+    element_map
+        .delete(Id(3), &mut |_, _| unreachable!())
+        .expect("Should ok");
+    element_map.focus(Id(2)).expect("Should ok");
+    element_map
+        .delete(Id(2), &mut |_, _| unreachable!())
+        .expect("Should ok");
+    assert_eq!(element_map.focused(), Some(Id(1)));
+    let _ = element_map.insert_new(Id(2), Id(1), InsertWay::Vertical, &mut |_, _| {});
+    let _ = element_map.drag_resize(-270., Direction::Bottom, Id(1), &mut |_, _| {});
+
+    // The tiling comes back
+    element_map.set_monocle(false, &mut |id, size_pos| {
+        assert!(tiling.contains(&(id, size_pos)));
+    });
+    assert_eq!(element_map.root().windows(), tiling);
+    assert!(element_map.placements().iter().all(|p| p.visible));
+}
@@ -0,0 +1,35 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{DispatchCallback, Result, TopElementMap, dispatch_changed};
+
+impl<T: MinusAbleMatUnit> TopElementMap<T> {
+    /// Run the operations in `op` as one.
+    ///
+    /// When `op` succeeds, every window whose size or position is changed by it is dispatched
+    /// once to `f`, with where it is at the end. When it fails, the map is put back as it was
+    /// before with its settings, and nothing is dispatched. The operations in `op` should be given
+    /// `&mut ()`, their changes come with the dispatch of the transaction. It is one step of the
+    /// undo history.
+    pub fn transaction<F, R, O>(&mut self, f: &mut F, op: O) -> Result<R>
+    where
+        F: DispatchCallback<T>,
+        O: FnOnce(&mut Self) -> Result<R>,
+    {
+        self.record(|map| {
+            let state = map.state();
+            // NOTE: the settings are not in the undo state, undo does not change them
+            let settings = (map.insert_policy, map.autotiling, map.auto_normalize);
+            let before = map.visible_geometry();
+            match op(map) {
+                Ok(result) => {
+                    dispatch_changed(&before, &map.visible_geometry(), f);
+                    Ok(result)
+                }
+                Err(error) => {
+                    map.reset(state);
+                    (map.insert_policy, map.autotiling, map.auto_normalize) = settings;
+                    Err(error)
+                }
+            }
+        })
+    }
+}
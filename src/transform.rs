@@ -0,0 +1,154 @@
+use crate::utils::MinusAbleMatUnit;
+use crate::{DispatchCallback, Element, InsertWay, Percentage, Rotation};
+
+impl<T: MinusAbleMatUnit> Element<T> {
+    /// Change the way of a container in place. The percent of the children is moved to the new
+    /// axis, then all the windows inside are remapped and dispatched.
+    /// Nothing happens if the element is not a container.
+    pub fn set_way<F>(&mut self, way: InsertWay, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let Some(old_way) = self.way() else {
+            return;
+        };
+        if old_way == way {
+            return;
+        }
+        if let Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } = self {
+            for element in elements.iter_mut() {
+                let share = element.percent().on_axis(old_way);
+                element.set_percentage(Percentage::along(way, share));
+            }
+        }
+        self.replace_way(way);
+        self.remap(self.size_pos(), f);
+    }
+
+    /// Turn a vertical container into a horizontal one, or the opposite
+    pub fn toggle_way<F>(&mut self, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        match self.way() {
+            Some(InsertWay::Vertical) => self.set_way(InsertWay::Horizontal, f),
+            Some(InsertWay::Horizontal) => self.set_way(InsertWay::Vertical, f),
+            None => {}
+        }
+    }
+
+    /// Swap the vertical and horizontal axis of everything inside the element, the order of the
+    /// elements is kept. Only the windows which are moved are dispatched.
+    pub fn transpose<F>(&mut self, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let before = self.windows();
+        self.transpose_tree();
+        self.remap_changed(&before, f);
+    }
+
+    /// Flip the element on the `way` axis: [`InsertWay::Horizontal`] flips it from left to right,
+    /// [`InsertWay::Vertical`] from top to bottom. Only the windows which are moved are
+    /// dispatched.
+    pub fn mirror<F>(&mut self, way: InsertWay, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let before = self.windows();
+        self.mirror_tree(way);
+        self.remap_changed(&before, f);
+    }
+
+    /// Rotate the element clockwise. Only the windows which are moved are dispatched.
+    pub fn rotate<F>(&mut self, rotation: Rotation, f: &mut F)
+    where
+        F: DispatchCallback<T>,
+    {
+        let before = self.windows();
+        match rotation {
+            // NOTE: the left side becomes the top side, the top side becomes the right side
+            Rotation::Deg90 => {
+                self.transpose_tree();
+                self.mirror_tree(InsertWay::Horizontal);
+            }
+            Rotation::Deg180 => {
+                self.mirror_tree(InsertWay::Horizontal);
+                self.mirror_tree(InsertWay::Vertical);
+            }
+            Rotation::Deg270 => {
+                self.transpose_tree();
+                self.mirror_tree(InsertWay::Vertical);
+            }
+        }
+        self.remap_changed(&before, f);
+    }
+
+    // NOTE: the percent of self is in the upper container, which is not transposed
+    fn transpose_tree(&mut self) {
+        let Some(way) = self.way() else {
+            return;
+        };
+        if let Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } = self {
+            for element in elements.iter_mut() {
+                let Percentage { width, height } = element.percent();
+                element.set_percentage(Percentage {
+                    width: height,
+                    height: width,
+                });
+                element.transpose_tree();
+            }
+        }
+        self.replace_way(match way {
+            InsertWay::Vertical => InsertWay::Horizontal,
+            InsertWay::Horizontal => InsertWay::Vertical,
+        });
+    }
+
+    fn mirror_tree(&mut self, way: InsertWay) {
+        let current_way = self.way();
+        if let Self::Vertical { elements, .. } | Self::Horizontal { elements, .. } = self {
+            if current_way == Some(way) {
+                elements.reverse();
+            }
+            for element in elements.iter_mut() {
+                element.mirror_tree(way);
+            }
+        }
+    }
+
+    /// Only change the kind of the container, nothing inside is touched
+    fn replace_way(&mut self, way: InsertWay) {
+        let (Self::Vertical {
+            elements,
+            size_pos,
+            percent,
+            pinned,
+        }
+        | Self::Horizontal {
+            elements,
+            size_pos,
+            percent,
+            pinned,
+        }) = self
+        else {
+            return;
+        };
+        let elements = std::mem::take(elements);
+        let (size_pos, percent, pinned) = (*size_pos, *percent, *pinned);
+        *self = match way {
+            InsertWay::Vertical => Self::Vertical {
+                elements,
+                size_pos,
+                percent,
+                pinned,
+            },
+            InsertWay::Horizontal => Self::Horizontal {
+                elements,
+                size_pos,
+                percent,
+                pinned,
+            },
+        };
+    }
+}
@@ -51,6 +51,7 @@ impl_minus_able_unit!(i32, 0, 2);
 impl_unit!(u32, 0, 2);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size<T = f32> {
     pub width: T,
     pub height: T,
@@ -123,6 +124,27 @@ impl Size<f32> {
             height: 1.,
         }
     }
+    /// The percentage of a child which takes `percent` of the container along the `way` axis,
+    /// and the whole of the other one
+    pub fn along(way: InsertWay, percent: f32) -> Self {
+        match way {
+            InsertWay::Horizontal => Self {
+                width: percent,
+                height: 1.,
+            },
+            InsertWay::Vertical => Self {
+                width: 1.,
+                height: percent,
+            },
+        }
+    }
+    /// The part of the percentage which is on the `way` axis
+    pub fn on_axis(&self, way: InsertWay) -> f32 {
+        match way {
+            InsertWay::Horizontal => self.width,
+            InsertWay::Vertical => self.height,
+        }
+    }
     /// This compute the change of the percent
     pub fn change_expand(&self, way: InsertWay) -> Self {
         match way {
@@ -171,6 +193,7 @@ impl<T: MinusAbleMatUnit> Size<T> {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position<T = f32> {
     pub x: T,
     pub y: T,
@@ -216,6 +239,7 @@ impl<T: MapUnit> Position<T> {
     }
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SizeAndPos<T = f32> {
     pub size: Size<T>,
     pub position: Position<T>,
@@ -254,6 +278,7 @@ impl<T: MapUnit> SubAssign for SizeAndPos<T> {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InsertWay {
     Vertical,
     #[default]
@@ -404,6 +429,7 @@ impl<T: MinusAbleMatUnit> SizeAndPos<T> {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Left,
     Right,
@@ -429,6 +455,14 @@ impl Direction {
     }
 }
 
+/// The clockwise rotation of a layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
 impl Direction {
     pub fn expend_way(insert_way: InsertWay, start: bool) -> Self {
         match (insert_way, start) {